@@ -0,0 +1,133 @@
+// Times the enemy grid against plain scans over every enemy, the way steering,
+// spawning and targeting used to work. Run with `cargo bench --bench spatial`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy_survivors::spatial::{GridEntry, SpatialGrid};
+
+const ENEMY_COUNT: usize = 5_000;
+const CELL_SIZE: f32 = 64.;
+// Enemies crowd around the pawn, so pack them into a disc about two screens wide
+const SWARM_RADIUS: f32 = 1_200.;
+const NEIGHBOR_RADIUS: f32 = 48.;
+const SPAWN_CLEARANCE: f32 = 24.;
+const TARGET_RANGE: f32 = 400.;
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    fastrand::seed(7);
+    let entries: Vec<GridEntry> = (0..ENEMY_COUNT)
+        .map(|index| GridEntry {
+            entity: Entity::from_raw(index as u32),
+            position: Vec2::from_angle(fastrand::f32() * std::f32::consts::TAU)
+                * fastrand::f32().sqrt()
+                * SWARM_RADIUS,
+            velocity: Vec2::new(fastrand::f32() - 0.5, fastrand::f32() - 0.5),
+            health: fastrand::f32() * 1_000.,
+        })
+        .collect();
+    let mut grid = SpatialGrid::new(CELL_SIZE);
+
+    println!("{} enemies, {} iterations each", ENEMY_COUNT, ITERATIONS);
+
+    report(
+        "rebuild",
+        "",
+        time(|| grid.rebuild(entries.iter().copied())),
+    );
+
+    // One neighbour lookup per enemy, as steering does every tick
+    let grid_neighbors = time(|| {
+        for entry in &entries {
+            black_box(grid.within(entry.position, NEIGHBOR_RADIUS).count());
+        }
+    });
+    let scan_neighbors = time(|| {
+        for entry in &entries {
+            black_box(
+                entries
+                    .iter()
+                    .filter(|other| other.position.distance(entry.position) <= NEIGHBOR_RADIUS)
+                    .count(),
+            );
+        }
+    });
+    report("neighbors x5000", "grid", grid_neighbors);
+    report("neighbors x5000", "scan", scan_neighbors);
+
+    let spots: Vec<Vec2> = (0..100)
+        .map(|_| (Vec2::new(fastrand::f32(), fastrand::f32()) - 0.5) * SWARM_RADIUS * 2.)
+        .collect();
+    let grid_spawns = time(|| {
+        for &spot in &spots {
+            black_box(grid.any_within(spot, SPAWN_CLEARANCE));
+        }
+    });
+    let scan_spawns = time(|| {
+        for &spot in &spots {
+            black_box(
+                entries
+                    .iter()
+                    .any(|entry| entry.position.distance(spot) <= SPAWN_CLEARANCE),
+            );
+        }
+    });
+    report("spawn checks x100", "grid", grid_spawns);
+    report("spawn checks x100", "scan", scan_spawns);
+
+    let grid_nearest = time(|| {
+        for &spot in &spots {
+            black_box(grid.nearest(spot, 3, TARGET_RANGE));
+        }
+    });
+    let scan_nearest = time(|| {
+        for &spot in &spots {
+            let mut found: Vec<(f32, GridEntry)> = entries
+                .iter()
+                .map(|entry| (entry.position.distance(spot), *entry))
+                .filter(|(distance, _)| *distance <= TARGET_RANGE)
+                .collect();
+            found.sort_by(|a, b| a.0.total_cmp(&b.0));
+            found.truncate(3);
+            black_box(found);
+        }
+    });
+    report("nearest 3 x100", "grid", grid_nearest);
+    report("nearest 3 x100", "scan", scan_nearest);
+
+    report(
+        "densest",
+        "grid",
+        time(|| {
+            black_box(grid.densest(Vec2::ZERO, TARGET_RANGE, CELL_SIZE));
+        }),
+    );
+
+    // The grid has to agree with a scan, or the speed is meaningless
+    for &spot in &spots {
+        let expected = entries
+            .iter()
+            .filter(|entry| entry.position.distance_squared(spot) <= TARGET_RANGE * TARGET_RANGE)
+            .count();
+        assert_eq!(grid.within(spot, TARGET_RANGE).count(), expected);
+    }
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn report(name: &str, method: &str, duration: Duration) {
+    println!(
+        "{:<20} {:<5} {:>10.3} ms",
+        name,
+        method,
+        duration.as_secs_f64() * 1_000.
+    );
+}
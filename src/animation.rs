@@ -1,8 +1,14 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+
+use crate::damage::{DamageDealt, DamageSet};
+use crate::AppState;
 
 pub struct AnimationPlugin;
 
-#[derive(Clone, Component, Debug)]
+// Simple looping range, used by weapon effects that only ever play one animation
+#[derive(Clone, Component, Debug, Deserialize)]
 pub struct AnimationIndices {
     pub first: usize,
     pub last: usize,
@@ -11,9 +17,199 @@ pub struct AnimationIndices {
 #[derive(Component, Deref, DerefMut)]
 pub struct AnimationTimer(pub Timer);
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum ClipName {
+    Idle,
+    Run,
+    Hit,
+    Death,
+    Attack,
+}
+
+impl ClipName {
+    fn default_mode(self) -> PlayMode {
+        match self {
+            ClipName::Idle | ClipName::Run => PlayMode::Loop,
+            ClipName::Hit | ClipName::Death | ClipName::Attack => PlayMode::Once,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+pub enum PlayMode {
+    #[default]
+    Loop,
+    // Holds the last frame and sends AnimationFinished
+    Once,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct AnimationClip {
+    pub first: usize,
+    pub last: usize,
+    // Seconds each frame stays up
+    #[serde(default = "default_frame_time")]
+    pub frame_time: f32,
+    // Left out, idle and run loop while the rest play once
+    #[serde(default)]
+    pub mode: Option<PlayMode>,
+}
+
+fn default_frame_time() -> f32 {
+    0.1
+}
+
+impl AnimationClip {
+    pub const fn new(first: usize, last: usize, frame_time: f32) -> Self {
+        AnimationClip {
+            first,
+            last,
+            frame_time,
+            mode: None,
+        }
+    }
+}
+
+// What the entity is doing from moment to moment, which picks its looping clip
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnimationState {
+    #[default]
+    Idle,
+    Moving,
+}
+
+impl AnimationState {
+    fn clip(self) -> ClipName {
+        match self {
+            AnimationState::Idle => ClipName::Idle,
+            AnimationState::Moving => ClipName::Run,
+        }
+    }
+}
+
+// Named clips for one sprite sheet and the one playing right now
+#[derive(Component, Clone, Debug)]
+pub struct AnimationController {
+    clips: HashMap<ClipName, AnimationClip>,
+    current: ClipName,
+    index: usize,
+    timer: Timer,
+    finished: bool,
+}
+
+impl AnimationController {
+    pub fn new(idle: AnimationClip) -> Self {
+        let mut clips = HashMap::default();
+        clips.insert(ClipName::Idle, idle);
+        AnimationController {
+            clips,
+            current: ClipName::Idle,
+            index: idle.first,
+            timer: Timer::from_seconds(idle.frame_time, TimerMode::Repeating),
+            finished: false,
+        }
+    }
+
+    pub fn with_clip(mut self, name: ClipName, clip: AnimationClip) -> Self {
+        self.clips.insert(name, clip);
+        self
+    }
+
+    pub fn has(&self, name: ClipName) -> bool {
+        self.clips.contains_key(&name)
+    }
+
+    pub fn current(&self) -> ClipName {
+        self.current
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn mode(&self) -> PlayMode {
+        self.clips[&self.current]
+            .mode
+            .unwrap_or(self.current.default_mode())
+    }
+
+    // A one-shot that is still running, which locomotion shouldn't cut off
+    pub fn is_busy(&self) -> bool {
+        self.mode() == PlayMode::Once && !self.finished
+    }
+
+    // Switches clips, leaving the current one alone if it's already playing
+    pub fn play(&mut self, name: ClipName) {
+        if name != self.current {
+            self.replay(name);
+        }
+    }
+
+    // Starts a clip from its first frame, even if it is the one playing
+    pub fn replay(&mut self, name: ClipName) {
+        let Some(clip) = self.clips.get(&name) else {
+            return;
+        };
+        self.current = name;
+        self.index = clip.first;
+        self.timer = Timer::from_seconds(clip.frame_time, TimerMode::Repeating);
+        self.finished = false;
+    }
+
+    // Returns true when a one-shot reaches its end
+    fn advance(&mut self, delta: std::time::Duration) -> bool {
+        if self.finished {
+            return false;
+        }
+
+        let clip = self.clips[&self.current];
+        self.timer.tick(delta);
+        for _ in 0..self.timer.times_finished_this_tick() {
+            if self.index < clip.last {
+                self.index += 1;
+            } else if self.mode() == PlayMode::Loop {
+                self.index = clip.first;
+            } else {
+                self.finished = true;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// Sent once a clip that plays once has shown its last frame
+#[derive(Event, Clone, Copy, Debug)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub clip: ClipName,
+}
+
+// Removed as soon as its one-shot clip finishes, like a corpse playing its death
+#[derive(Component)]
+pub struct DespawnOnFinish;
+
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, animate_sprites);
+        app.add_event::<AnimationFinished>()
+            .add_systems(
+                Update,
+                (
+                    animate_sprites,
+                    (drive_animations, advance_animations, despawn_finished).chain(),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                play_hit_clips
+                    .after(DamageSet)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_finishing);
     }
 }
 
@@ -32,3 +228,147 @@ fn animate_sprites(
         }
     }
 }
+
+fn drive_animations(mut query: Query<(&AnimationState, &mut AnimationController)>) {
+    for (state, mut controller) in &mut query {
+        // Nothing comes back from a death
+        if controller.is_busy() || controller.current() == ClipName::Death {
+            continue;
+        }
+
+        let clip = state.clip();
+        if controller.has(clip) {
+            controller.play(clip);
+        } else {
+            controller.play(ClipName::Idle);
+        }
+    }
+}
+
+fn advance_animations(
+    time: Res<Time>,
+    mut finished: EventWriter<AnimationFinished>,
+    mut query: Query<(Entity, &mut AnimationController, &mut TextureAtlas)>,
+) {
+    for (entity, mut controller, mut atlas) in &mut query {
+        if controller.advance(time.delta()) {
+            finished.send(AnimationFinished {
+                entity,
+                clip: controller.current(),
+            });
+        }
+        if atlas.index != controller.index() {
+            atlas.index = controller.index();
+        }
+    }
+}
+
+fn despawn_finished(
+    mut commands: Commands,
+    mut finished: EventReader<AnimationFinished>,
+    query: Query<(), With<DespawnOnFinish>>,
+) {
+    for event in finished.read() {
+        if query.contains(event.entity) {
+            commands.entity(event.entity).despawn();
+        }
+    }
+}
+
+fn play_hit_clips(
+    mut damage: EventReader<DamageDealt>,
+    mut query: Query<&mut AnimationController>,
+) {
+    for event in damage.read() {
+        let Ok(mut controller) = query.get_mut(event.target) else {
+            continue;
+        };
+        // Hits landing while a one-shot plays are let through, or steady damage
+        // would keep restarting the hit clip on its first frame
+        if controller.has(ClipName::Hit)
+            && !controller.is_busy()
+            && controller.current() != ClipName::Death
+        {
+            controller.replay(ClipName::Hit);
+        }
+    }
+}
+
+fn cleanup_finishing(mut commands: Commands, query: Query<Entity, With<DespawnOnFinish>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    const FRAME_TIME: f32 = 0.25;
+    const FRAME: Duration = Duration::from_millis(250);
+
+    fn controller() -> AnimationController {
+        AnimationController::new(AnimationClip::new(0, 2, FRAME_TIME))
+            .with_clip(ClipName::Hit, AnimationClip::new(3, 4, FRAME_TIME))
+    }
+
+    #[test]
+    fn looping_clips_wrap_to_their_first_frame() {
+        let mut controller = controller();
+        for expected in [1, 2, 0, 1] {
+            assert!(!controller.advance(FRAME));
+            assert_eq!(controller.index(), expected);
+        }
+
+        // A long frame steps through as many frames as fit in it
+        assert!(!controller.advance(FRAME * 3));
+        assert_eq!(controller.index(), 1);
+        assert!(!controller.is_busy());
+    }
+
+    #[test]
+    fn once_clips_hold_their_last_frame_and_finish_once() {
+        let mut controller = controller();
+        controller.play(ClipName::Hit);
+        assert_eq!(controller.index(), 3);
+        assert!(controller.is_busy());
+
+        assert!(!controller.advance(FRAME));
+        assert_eq!(controller.index(), 4);
+        assert!(controller.advance(FRAME));
+        assert!(controller.is_finished());
+        assert!(!controller.is_busy());
+
+        assert!(!controller.advance(FRAME * 4));
+        assert_eq!(controller.index(), 4);
+    }
+
+    #[test]
+    fn finishing_a_once_clip_sends_one_event() {
+        let mut world = World::new();
+        world.init_resource::<Events<AnimationFinished>>();
+        let mut time = Time::<()>::default();
+        time.advance_by(FRAME * 2);
+        world.insert_resource(time);
+
+        let mut controller = controller();
+        controller.play(ClipName::Hit);
+        let entity = world.spawn((controller, TextureAtlas::default())).id();
+
+        world.run_system_once(advance_animations);
+        world.run_system_once(advance_animations);
+
+        let events = world.resource::<Events<AnimationFinished>>();
+        let sent: Vec<(Entity, ClipName)> = events
+            .get_reader()
+            .read(events)
+            .map(|event| (event.entity, event.clip))
+            .collect();
+        assert_eq!(sent, vec![(entity, ClipName::Hit)]);
+        assert_eq!(world.get::<TextureAtlas>(entity).unwrap().index, 4);
+    }
+}
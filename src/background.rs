@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+use crate::constants::*;
 use crate::AppState;
 
 #[derive(Resource)]
@@ -30,7 +31,10 @@ fn setup_background(
     rapier_config.gravity = Vec2::ZERO;
 
     let texture_handle: Handle<Image> = asset_server.load("floors/tiles.png");
-    let map_size = TilemapSize { x: 640, y: 480 };
+    let map_size = TilemapSize {
+        x: MAP_TILES_X,
+        y: MAP_TILES_Y,
+    };
     let tilemap_entity = commands.spawn_empty().id();
     let mut tile_storage = TileStorage::empty(map_size);
 
@@ -49,7 +53,10 @@ fn setup_background(
         }
     }
 
-    let tile_size = TilemapTileSize { x: 16., y: 16. };
+    let tile_size = TilemapTileSize {
+        x: TILE_SIZE,
+        y: TILE_SIZE,
+    };
     let grid_size = tile_size.into();
     let map_type = TilemapType::default();
 
@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::animation::{AnimationController, ClipName};
+use crate::collision::EnemyHitPlayer;
+use crate::components::{Enemy, Pawn};
+use crate::elite::Elite;
+use crate::enemy::{move_enemies, EnemyBehavior, EnemySet, EnemySprite, HitStun};
+use crate::status::StatusApplication;
+use crate::AppState;
+
+const TELEGRAPH_COLOR: Color = Color::ORANGE_RED;
+const PROJECTILE_COLOR: Color = Color::rgb(0.6, 1., 0.6);
+const PROJECTILE_LIFETIME: f32 = 4.;
+
+pub struct BehaviorPlugin;
+
+impl Plugin for BehaviorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                (charge, keep_distance_and_shoot, teleport)
+                    .in_set(EnemySet::Steering)
+                    .after(move_enemies),
+                projectile_hit_player,
+                expire_projectiles,
+            )
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(OnExit(AppState::InGame), cleanup_projectiles);
+    }
+}
+
+#[derive(Component, Default)]
+pub struct BehaviorState {
+    pub phase: BehaviorPhase,
+    pub cooldown: Timer,
+}
+
+impl BehaviorState {
+    pub fn is_telegraphing(&self) -> bool {
+        matches!(self.phase, BehaviorPhase::Telegraphing(_))
+    }
+}
+
+#[derive(Default)]
+pub enum BehaviorPhase {
+    #[default]
+    Moving,
+    Telegraphing(Timer),
+    Dashing(Timer, Vec2),
+}
+
+#[derive(Component)]
+pub struct EnemyProjectile {
+    pub damage: f32,
+    pub status: Option<StatusApplication>,
+    lifetime: Timer,
+}
+
+fn charge(
+    time: Res<Time>,
+    player: Query<&Transform, (With<Pawn>, Without<Enemy>)>,
+    mut enemies: Query<
+        (
+            &Transform,
+            &mut Velocity,
+            &mut Sprite,
+            &EnemySprite,
+            &mut BehaviorState,
+            &mut AnimationController,
+            Option<&Elite>,
+        ),
+        (With<Enemy>, Without<HitStun>),
+    >,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+
+    for (transform, mut velocity, mut sprite, enemy, mut state, mut animation, elite) in
+        &mut enemies
+    {
+        let EnemyBehavior::Charger {
+            range,
+            telegraph,
+            dash_speed,
+            dash_duration,
+            cooldown,
+        } = enemy.behavior
+        else {
+            continue;
+        };
+
+        state.cooldown.tick(time.delta());
+        let to_player = player_pos - transform.translation.truncate();
+
+        match &mut state.phase {
+            BehaviorPhase::Moving => {
+                if state.cooldown.finished() && to_player.length() < range {
+                    state.phase = BehaviorPhase::Telegraphing(Timer::from_seconds(
+                        telegraph,
+                        TimerMode::Once,
+                    ));
+                    sprite.color = TELEGRAPH_COLOR;
+                    velocity.linvel = Vec2::ZERO;
+                    animation.replay(ClipName::Attack);
+                }
+            }
+            BehaviorPhase::Telegraphing(timer) => {
+                velocity.linvel = Vec2::ZERO;
+                if timer.tick(time.delta()).finished() {
+                    // The dash direction is locked in once the telegraph ends
+                    state.phase = BehaviorPhase::Dashing(
+                        Timer::from_seconds(dash_duration, TimerMode::Once),
+                        to_player.normalize_or_zero(),
+                    );
+                    sprite.color = elite.map_or(Color::WHITE, |elite| elite.tint);
+                }
+            }
+            BehaviorPhase::Dashing(timer, direction) => {
+                velocity.linvel = *direction * dash_speed;
+                sprite.flip_x = direction.x < 0.;
+                if timer.tick(time.delta()).finished() {
+                    state.phase = BehaviorPhase::Moving;
+                    state.cooldown = Timer::from_seconds(cooldown, TimerMode::Once);
+                }
+            }
+        }
+    }
+}
+
+fn keep_distance_and_shoot(
+    mut commands: Commands,
+    time: Res<Time>,
+    player: Query<&Transform, (With<Pawn>, Without<Enemy>)>,
+    mut enemies: Query<
+        (
+            &Transform,
+            &mut Velocity,
+            &EnemySprite,
+            &mut BehaviorState,
+            &mut AnimationController,
+        ),
+        (With<Enemy>, Without<HitStun>),
+    >,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+
+    for (transform, mut velocity, enemy, mut state, mut animation) in &mut enemies {
+        let EnemyBehavior::Ranged {
+            preferred_distance,
+            range,
+            cooldown,
+            projectile_speed,
+            projectile_damage,
+            projectile_status,
+        } = enemy.behavior
+        else {
+            continue;
+        };
+
+        let position = transform.translation.truncate();
+        let to_player = player_pos - position;
+        let distance = to_player.length();
+        let direction = to_player.normalize_or_zero();
+
+        if distance < preferred_distance {
+            velocity.linvel = -direction * enemy.speed;
+        }
+
+        state.cooldown.tick(time.delta());
+        if state.cooldown.finished() && distance <= range {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: PROJECTILE_COLOR,
+                        custom_size: Some(Vec2::splat(6.)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position.extend(3.)),
+                    ..default()
+                },
+                EnemyProjectile {
+                    damage: projectile_damage,
+                    status: projectile_status,
+                    lifetime: Timer::from_seconds(PROJECTILE_LIFETIME, TimerMode::Once),
+                },
+                RigidBody::KinematicVelocityBased,
+                Velocity::linear(direction * projectile_speed),
+                Collider::ball(3.),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+            ));
+            state.cooldown = Timer::from_seconds(cooldown, TimerMode::Once);
+            animation.replay(ClipName::Attack);
+        }
+    }
+}
+
+fn teleport(
+    time: Res<Time>,
+    player: Query<&Transform, (With<Pawn>, Without<Enemy>)>,
+    mut enemies: Query<
+        (&mut Transform, &EnemySprite, &mut BehaviorState),
+        (With<Enemy>, Without<HitStun>),
+    >,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+
+    for (mut transform, enemy, mut state) in &mut enemies {
+        let EnemyBehavior::Teleporter {
+            trigger_distance,
+            blink_distance,
+            cooldown,
+        } = enemy.behavior
+        else {
+            continue;
+        };
+
+        state.cooldown.tick(time.delta());
+        let distance = player_pos.distance(transform.translation.truncate());
+        if state.cooldown.finished() && distance > trigger_distance {
+            let offset = Vec2::from_angle(fastrand::f32() * std::f32::consts::TAU) * blink_distance;
+            transform.translation = (player_pos + offset).extend(transform.translation.z);
+            state.cooldown = Timer::from_seconds(cooldown, TimerMode::Once);
+        }
+    }
+}
+
+fn projectile_hit_player(
+    mut commands: Commands,
+    mut events: EventReader<EnemyHitPlayer>,
+    projectiles: Query<Entity, With<EnemyProjectile>>,
+) {
+    for EnemyHitPlayer(entity) in events.read() {
+        if let Ok(projectile) = projectiles.get(*entity) {
+            commands.entity(projectile).despawn();
+        }
+    }
+}
+
+fn expire_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectiles: Query<(Entity, &mut EnemyProjectile)>,
+) {
+    for (entity, mut projectile) in &mut projectiles {
+        if projectile.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn cleanup_projectiles(mut commands: Commands, query: Query<Entity, With<EnemyProjectile>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
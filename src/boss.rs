@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+
+use bevy::asset::ron;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+use crate::components::{Enemy, Pawn};
+use crate::constants::*;
+use crate::damage::{DamageEvent, DamageSet, DamageType};
+use crate::enemy::{spawn_enemy, EnemyDefinitions, EnemySprite, SpawnEnemy};
+use crate::indicator::Tracked;
+use crate::spatial::{EnemyGrid, SpatialSet};
+use crate::spawning::{SpawnPlacement, SpawnSide};
+use crate::{AppState, RunTime};
+
+const ARENA_COLOR: Color = Color::ORANGE_RED;
+const TELEGRAPH_COLOR: Color = Color::rgba(1., 0.2, 0.1, 0.8);
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_boss_definitions)
+            .add_systems(OnEnter(AppState::InGame), reset_boss_schedule)
+            .add_systems(
+                FixedUpdate,
+                (
+                    spawn_scheduled_bosses,
+                    advance_boss_phases,
+                    boss_summons,
+                    boss_area_attacks,
+                    resolve_area_attacks,
+                    release_arena,
+                )
+                    .chain()
+                    .after(SpatialSet)
+                    .before(DamageSet)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            // After rapier writes the pawn's position back, so the clamp isn't overwritten
+            .add_systems(
+                FixedUpdate,
+                enforce_arena
+                    .after(PhysicsSet::Writeback)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (draw_arena, draw_area_attacks).run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_encounters);
+    }
+}
+
+#[derive(Deserialize)]
+struct BossData {
+    schedule: Vec<ScheduledBoss>,
+    bosses: HashMap<String, BossDefinition>,
+}
+
+#[derive(Clone, Deserialize)]
+struct ScheduledBoss {
+    // Seconds into the run
+    at: f32,
+    boss: String,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct BossDefinition {
+    title: String,
+    enemy: String,
+    health: f32,
+    scale: f32,
+    #[serde(default)]
+    arena_radius: Option<f32>,
+    phases: Vec<BossPhase>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct BossPhase {
+    // Fraction of max health at which this phase starts
+    threshold: f32,
+    #[serde(default = "default_speed")]
+    speed: f32,
+    #[serde(default)]
+    summon: Option<Summon>,
+    #[serde(default)]
+    area_attack: Option<AreaAttackDefinition>,
+}
+
+fn default_speed() -> f32 {
+    1.
+}
+
+#[derive(Clone, Deserialize)]
+pub struct Summon {
+    enemy: String,
+    count: usize,
+    interval: f32,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub struct AreaAttackDefinition {
+    radius: f32,
+    telegraph: f32,
+    interval: f32,
+    damage: f32,
+}
+
+#[derive(Resource)]
+struct BossDefinitions(HashMap<String, BossDefinition>);
+
+#[derive(Resource)]
+struct BossSchedule {
+    entries: Vec<ScheduledBoss>,
+    next: usize,
+}
+
+#[derive(Component)]
+pub struct Boss {
+    pub title: String,
+    pub max_health: f32,
+    base_speed: f32,
+    phases: Vec<BossPhase>,
+    phase: usize,
+    summon_timer: Timer,
+    attack_timer: Timer,
+}
+
+impl Boss {
+    fn current_phase(&self) -> &BossPhase {
+        &self.phases[self.phase]
+    }
+
+    fn enter_phase(&mut self, phase: usize) {
+        self.phase = phase;
+        let phase = &self.phases[phase];
+        self.summon_timer = phase.summon.as_ref().map_or_else(Timer::default, |summon| {
+            Timer::from_seconds(summon.interval, TimerMode::Repeating)
+        });
+        self.attack_timer = phase.area_attack.map_or_else(Timer::default, |attack| {
+            Timer::from_seconds(attack.interval, TimerMode::Repeating)
+        });
+    }
+
+    fn speed(&self) -> f32 {
+        self.base_speed * self.current_phase().speed
+    }
+}
+
+#[derive(Component)]
+pub struct Arena {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+// Telegraphed blast that hurts the pawn if it is still inside when the timer ends
+#[derive(Component)]
+pub struct AreaAttack {
+    radius: f32,
+    damage: f32,
+    damage_type: DamageType,
+    source: Option<Entity>,
+    // Also catches enemies standing in the blast
+    hurts_enemies: bool,
+    timer: Timer,
+}
+
+impl AreaAttack {
+    pub fn new(radius: f32, damage: f32, telegraph: f32) -> Self {
+        AreaAttack {
+            radius,
+            damage,
+            damage_type: DamageType::Physical,
+            source: None,
+            hurts_enemies: false,
+            timer: Timer::from_seconds(telegraph, TimerMode::Once),
+        }
+    }
+
+    pub fn hurting_enemies(mut self) -> Self {
+        self.hurts_enemies = true;
+        self
+    }
+
+    pub fn with_source(mut self, source: Entity, damage_type: DamageType) -> Self {
+        self.source = Some(source);
+        self.damage_type = damage_type;
+        self
+    }
+}
+
+fn load_boss_definitions(mut commands: Commands) {
+    let data: BossData = ron::from_str(include_str!("../assets/data/bosses.ron"))
+        .expect("Failed to parse boss definitions");
+
+    let mut entries = data.schedule;
+    entries.sort_by(|a, b| a.at.total_cmp(&b.at));
+
+    commands.insert_resource(BossDefinitions(data.bosses));
+    commands.insert_resource(BossSchedule { entries, next: 0 });
+}
+
+fn reset_boss_schedule(mut schedule: ResMut<BossSchedule>) {
+    schedule.next = 0;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_scheduled_bosses(
+    mut commands: Commands,
+    run_time: Res<RunTime>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut schedule: ResMut<BossSchedule>,
+    bosses: Res<BossDefinitions>,
+    enemies: Res<EnemyDefinitions>,
+    player: Query<&Transform, With<Pawn>>,
+    placement: SpawnPlacement,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    while let Some(entry) = schedule.entries.get(schedule.next).cloned() {
+        if run_time.elapsed_secs() < entry.at {
+            break;
+        }
+        schedule.next += 1;
+
+        let Some(definition) = bosses.0.get(&entry.boss) else {
+            warn!("Unknown boss definition: {}", entry.boss);
+            continue;
+        };
+        let Some(base) = enemies.get(&definition.enemy) else {
+            warn!("Unknown enemy definition: {}", definition.enemy);
+            continue;
+        };
+
+        let mut boss = Boss {
+            title: definition.title.clone(),
+            max_health: definition.health,
+            base_speed: base.speed,
+            phases: definition.phases.clone(),
+            phase: 0,
+            summon_timer: Timer::default(),
+            attack_timer: Timer::default(),
+        };
+        boss.enter_phase(0);
+
+        let mut enemy = base.clone();
+        enemy.health = definition.health;
+        enemy.speed = boss.speed();
+        enemy.knockback_resistance = 1.;
+
+        // Bosses walk in from where the pawn is heading, or the edge of the screen if that's full
+        let center = player.translation.truncate();
+        let position = placement.find(SpawnSide::Ahead).unwrap_or_else(|| {
+            let offset = Vec2::from_angle(fastrand::f32() * std::f32::consts::TAU) * (HEIGHT / 2.);
+            (center + offset).extend(2.)
+        });
+
+        let entity = spawn_enemy(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlas_layouts,
+            &enemy,
+            position,
+        );
+        commands.entity(entity).insert((
+            Transform::from_translation(position).with_scale(Vec3::splat(definition.scale)),
+            boss,
+            Tracked {
+                color: Color::CRIMSON,
+            },
+        ));
+
+        if let Some(radius) = definition.arena_radius {
+            commands.spawn(Arena { center, radius });
+        }
+    }
+}
+
+fn advance_boss_phases(mut bosses: Query<(&mut Boss, &mut EnemySprite)>) {
+    for (mut boss, mut enemy) in &mut bosses {
+        let fraction = enemy.health / boss.max_health;
+        let mut phase = boss.phase;
+
+        while phase + 1 < boss.phases.len() && fraction <= boss.phases[phase + 1].threshold {
+            phase += 1;
+        }
+
+        if phase != boss.phase {
+            boss.enter_phase(phase);
+            enemy.speed = boss.speed();
+        }
+    }
+}
+
+fn boss_summons(
+    time: Res<Time>,
+    mut bosses: Query<(&Transform, &mut Boss)>,
+    mut spawn_events: EventWriter<SpawnEnemy>,
+) {
+    for (transform, mut boss) in &mut bosses {
+        let Some(summon) = boss.current_phase().summon.clone() else {
+            continue;
+        };
+
+        if boss.summon_timer.tick(time.delta()).just_finished() {
+            for _ in 0..summon.count {
+                let offset = Vec2::from_angle(fastrand::f32() * std::f32::consts::TAU) * 48.;
+                spawn_events.send(SpawnEnemy {
+                    name: summon.enemy.clone(),
+                    position: transform.translation.truncate().extend(2.) + offset.extend(0.),
+                });
+            }
+        }
+    }
+}
+
+fn boss_area_attacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bosses: Query<(Entity, &mut Boss)>,
+    player: Query<&Transform, With<Pawn>>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    for (entity, mut boss) in &mut bosses {
+        let Some(attack) = boss.current_phase().area_attack else {
+            continue;
+        };
+
+        if boss.attack_timer.tick(time.delta()).just_finished() {
+            // Aim at where the pawn is now so it has the telegraph time to get out
+            commands.spawn((
+                TransformBundle::from_transform(Transform::from_translation(
+                    player.translation.truncate().extend(1.),
+                )),
+                AreaAttack::new(attack.radius, attack.damage, attack.telegraph)
+                    .with_source(entity, DamageType::Physical),
+            ));
+        }
+    }
+}
+
+fn resolve_area_attacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut damage_events: EventWriter<DamageEvent>,
+    grid: Res<EnemyGrid>,
+    mut attacks: Query<(Entity, &Transform, &mut AreaAttack), Without<Pawn>>,
+    player: Query<(Entity, &Transform), With<Pawn>>,
+) {
+    for (entity, transform, mut attack) in &mut attacks {
+        if !attack.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        if let Ok((player, player_transform)) = player.get_single() {
+            let distance = player_transform
+                .translation
+                .truncate()
+                .distance(transform.translation.truncate());
+            if distance <= attack.radius {
+                damage_events.send(DamageEvent {
+                    target: player,
+                    amount: attack.damage,
+                    crit: false,
+                    source: attack.source,
+                    damage_type: attack.damage_type,
+                });
+            }
+        }
+        if attack.hurts_enemies {
+            let center = transform.translation.truncate();
+            for enemy in grid.within(center, attack.radius) {
+                damage_events.send(DamageEvent {
+                    target: enemy.entity,
+                    amount: attack.damage,
+                    crit: false,
+                    source: attack.source,
+                    damage_type: attack.damage_type,
+                });
+            }
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+// Keep the pawn inside the ring while a boss holds the arena
+fn enforce_arena(arenas: Query<&Arena>, mut player: Query<&mut Transform, With<Pawn>>) {
+    let Ok(mut transform) = player.get_single_mut() else {
+        return;
+    };
+
+    for arena in &arenas {
+        let offset = transform.translation.truncate() - arena.center;
+        if offset.length() > arena.radius {
+            let clamped = arena.center + offset.clamp_length_max(arena.radius);
+            transform.translation = clamped.extend(transform.translation.z);
+        }
+    }
+}
+
+fn release_arena(
+    mut commands: Commands,
+    bosses: Query<(), (With<Boss>, With<Enemy>)>,
+    arenas: Query<Entity, With<Arena>>,
+) {
+    if !bosses.is_empty() {
+        return;
+    }
+
+    for entity in &arenas {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn draw_arena(mut gizmos: Gizmos, arenas: Query<&Arena>) {
+    for arena in &arenas {
+        gizmos
+            .circle_2d(arena.center, arena.radius, ARENA_COLOR)
+            .segments(96);
+    }
+}
+
+fn draw_area_attacks(mut gizmos: Gizmos, attacks: Query<(&Transform, &AreaAttack)>) {
+    for (transform, attack) in &attacks {
+        let position = transform.translation.truncate();
+        gizmos.circle_2d(position, attack.radius, TELEGRAPH_COLOR);
+        gizmos.circle_2d(
+            position,
+            attack.radius * attack.timer.fraction(),
+            TELEGRAPH_COLOR,
+        );
+    }
+}
+
+fn cleanup_encounters(
+    mut commands: Commands,
+    arenas: Query<Entity, With<Arena>>,
+    attacks: Query<Entity, With<AreaAttack>>,
+) {
+    for entity in arenas.iter().chain(&attacks) {
+        commands.entity(entity).despawn();
+    }
+}
@@ -1,7 +1,8 @@
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 use bevy_rapier2d::prelude::*;
 
-use crate::{components::Pawn, weapon::Weapon};
+use crate::{components::Pawn, weapon::Hitbox, AppState};
 
 pub struct CollisionPlugin;
 
@@ -9,7 +10,9 @@ impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<EnemyHitPlayer>()
             .add_event::<EnemyHitWeapon>()
-            .add_systems(Update, (enemy_collide_player, enemy_collide_weapon));
+            .init_resource::<WeaponContacts>()
+            .add_systems(Update, (enemy_collide_player, enemy_collide_weapon))
+            .add_systems(OnExit(AppState::InGame), clear_weapon_contacts);
     }
 }
 
@@ -19,8 +22,12 @@ pub struct EnemyHitPlayer(pub Entity);
 #[derive(Event)]
 pub struct EnemyHitWeapon(pub Entity);
 
+#[derive(Component)]
+pub struct TouchingPawn;
+
 // Enemies collide with player
 fn enemy_collide_player(
+    mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
     player: Query<Entity, With<Pawn>>,
     mut enemy_hit_player: EventWriter<EnemyHitPlayer>,
@@ -32,42 +39,66 @@ fn enemy_collide_player(
     let player = player.single();
 
     for event in collision_events.read() {
-        if let CollisionEvent::Started(entity1, entity2, _) = event {
-            if player == *entity1 {
-                enemy_hit_player.send(EnemyHitPlayer(*entity2));
+        match event {
+            CollisionEvent::Started(entity1, entity2, _) => {
+                let other = if player == *entity1 {
+                    *entity2
+                } else if player == *entity2 {
+                    *entity1
+                } else {
+                    continue;
+                };
+                enemy_hit_player.send(EnemyHitPlayer(other));
+                if let Some(mut entity_commands) = commands.get_entity(other) {
+                    entity_commands.try_insert(TouchingPawn);
+                }
+            }
+            CollisionEvent::Stopped(entity1, entity2, _) => {
+                let other = if player == *entity1 {
+                    *entity2
+                } else if player == *entity2 {
+                    *entity1
+                } else {
+                    continue;
+                };
+                if let Some(mut entity_commands) = commands.get_entity(other) {
+                    entity_commands.remove::<TouchingPawn>();
+                }
             }
         }
     }
 }
 
-#[derive(Component)]
-pub struct Collided;
+// Hitbox/enemy pairs that currently overlap
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct WeaponContacts(pub HashSet<(Entity, Entity)>);
 
 // Enemies collide with weapon
 fn enemy_collide_weapon(
-    mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
-    weapon: Query<Entity, With<Weapon>>,
+    weapons: Query<Entity, With<Hitbox>>,
+    mut contacts: ResMut<WeaponContacts>,
     mut enemy_hit_player: EventWriter<EnemyHitWeapon>,
 ) {
-    if weapon.is_empty() {
-        return;
-    }
-
-    let weapon = weapon.single();
-
     for event in collision_events.read() {
-        if let CollisionEvent::Started(entity1, entity2, _) = event {
-            if weapon == *entity1 {
-                commands.entity(*entity2).insert(Collided);
-                enemy_hit_player.send(EnemyHitWeapon(*entity2));
-            }
-        } else if let CollisionEvent::Stopped(entity1, entity2, _) = event {
-            if weapon == *entity1 {
-                if let Some(mut entity_commands) = commands.get_entity(*entity2) {
-                    entity_commands.remove::<Collided>();
+        match event {
+            CollisionEvent::Started(entity1, entity2, _) => {
+                if weapons.contains(*entity1) {
+                    contacts.insert((*entity1, *entity2));
+                    enemy_hit_player.send(EnemyHitWeapon(*entity2));
+                } else if weapons.contains(*entity2) {
+                    contacts.insert((*entity2, *entity1));
+                    enemy_hit_player.send(EnemyHitWeapon(*entity1));
                 }
             }
+            CollisionEvent::Stopped(entity1, entity2, _) => {
+                contacts.remove(&(*entity1, *entity2));
+                contacts.remove(&(*entity2, *entity1));
+            }
         }
     }
 }
+
+fn clear_weapon_contacts(mut contacts: ResMut<WeaponContacts>) {
+    contacts.clear();
+}
@@ -4,6 +4,22 @@ use bevy::prelude::*;
 pub struct Pawn {
     pub speed: f32,
     pub health: f32,
+    pub max_health: f32,
+    // Health restored every second
+    pub regen: f32,
+    // Flat reduction applied to every hit the pawn takes
+    pub armor: f32,
+    // Pickups closer than this start flying toward the pawn
+    pub magnet: f32,
+}
+
+impl Pawn {
+    // Returns how much health was actually restored
+    pub fn heal(&mut self, amount: f32) -> f32 {
+        let before = self.health;
+        self.health = (self.health + amount).min(self.max_health);
+        self.health - before
+    }
 }
 
 #[derive(Component)]
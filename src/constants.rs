@@ -6,11 +6,23 @@ pub const UI_LAYER: RenderLayers = RenderLayers::layer(9);
 pub const WIDTH: f32 = 640.;
 pub const HEIGHT: f32 = 480.;
 
+// The floor is a fixed tilemap centered on the origin
+pub const MAP_TILES_X: u32 = 640;
+pub const MAP_TILES_Y: u32 = 480;
+pub const TILE_SIZE: f32 = 16.;
+
 pub const SPRITE_WIDTH: u32 = 16;
 pub const SPRITE_HEIGHT: u32 = 16;
 
+pub const FIXED_TIMESTEP_HZ: f64 = 64.;
+// Seconds survived before the reaper ends the run in victory
+pub const DEFAULT_RUN_LENGTH: f32 = 30. * 60.;
+
 pub const PAWN_SPEED: f32 = 200.;
 pub const PAWN_SPEED_FAST: f32 = 300.;
+pub const PAWN_HEALTH: f32 = 100.;
+pub const PAWN_REGEN: f32 = 0.2;
+pub const PAWN_MAGNET: f32 = 48.;
 
 pub const ENEMY_WEAPON_GROUP: Group = Group::empty();
 pub const PAWN_WEAPON_GROUP: Group = Group::empty();
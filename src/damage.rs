@@ -0,0 +1,318 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::components::Pawn;
+use crate::elite::Shield;
+use crate::enemy::{EnemySet, EnemySprite};
+use crate::pawn::Invincible;
+use crate::settings::Settings;
+use crate::AppState;
+
+// Armor never reduces a hit below this, unless the hit itself was smaller
+const MIN_DAMAGE: f32 = 1.;
+
+// Floating numbers are recycled from a fixed pool instead of spawned per hit
+const DAMAGE_NUMBER_POOL_SIZE: usize = 96;
+const DAMAGE_NUMBER_LIFETIME: f32 = 0.7;
+const DAMAGE_NUMBER_RISE: f32 = 32.;
+const DAMAGE_NUMBER_SIZE: f32 = 10.;
+const CRIT_NUMBER_SIZE: f32 = 14.;
+const CRIT_COLOR: Color = Color::GOLD;
+const DAMAGE_NUMBERS_KEY: KeyCode = KeyCode::KeyN;
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<DamageDealt>()
+            .add_event::<DeathEvent>()
+            .init_resource::<DamageNumberPool>()
+            .configure_sets(
+                FixedUpdate,
+                DamageSet
+                    .after(EnemySet::Damage)
+                    .before(EnemySet::Death)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(FixedUpdate, resolve_damage.in_set(DamageSet))
+            .add_systems(OnEnter(AppState::InGame), setup_damage_numbers)
+            .add_systems(
+                Update,
+                (
+                    toggle_damage_numbers,
+                    show_damage_numbers,
+                    animate_damage_numbers,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_damage_numbers);
+    }
+}
+
+// Every damage source sends its events before this set and every reaction runs after it
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DamageSet;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Ice,
+    Poison,
+    Bleed,
+}
+
+impl DamageType {
+    fn color(self) -> Color {
+        match self {
+            DamageType::Physical => Color::WHITE,
+            DamageType::Fire => Color::ORANGE,
+            DamageType::Ice => Color::rgb(0.6, 0.9, 1.),
+            DamageType::Poison => Color::LIME_GREEN,
+            DamageType::Bleed => Color::CRIMSON,
+        }
+    }
+}
+
+// Raw damage aimed at an enemy or the pawn, before armor, resistances and shields
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub crit: bool,
+    pub source: Option<Entity>,
+    pub damage_type: DamageType,
+}
+
+// The part of a DamageEvent that actually came off the target's health
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageDealt {
+    pub target: Entity,
+    // Where the target stood, since a killing blow despawns it before anyone reads this
+    pub position: Vec2,
+    pub amount: f32,
+    pub crit: bool,
+    pub source: Option<Entity>,
+    pub damage_type: DamageType,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub killer: Option<Entity>,
+    pub damage_type: DamageType,
+}
+
+#[derive(Resource, Default)]
+struct DamageNumberPool {
+    entities: Vec<Entity>,
+    next: usize,
+}
+
+#[derive(Component)]
+struct DamageNumber {
+    lifetime: Timer,
+}
+
+fn resolve_damage(
+    mut events: EventReader<DamageEvent>,
+    mut dealt_events: EventWriter<DamageDealt>,
+    mut death_events: EventWriter<DeathEvent>,
+    mut enemies: Query<(&Transform, &mut EnemySprite, Option<&mut Shield>), Without<Pawn>>,
+    mut player: Query<(&Transform, &mut Pawn, Has<Invincible>)>,
+) {
+    for event in events.read() {
+        let (transform, amount, health) =
+            if let Ok((transform, mut enemy, shield)) = enemies.get_mut(event.target) {
+                // Already killed earlier this tick
+                if enemy.health <= 0. {
+                    continue;
+                }
+
+                let resistance = enemy
+                    .resistances
+                    .get(&event.damage_type)
+                    .copied()
+                    .unwrap_or(0.);
+                let mut amount = event.amount * (1. - resistance.min(1.));
+                if let Some(mut shield) = shield {
+                    let absorbed = amount.min(shield.0);
+                    shield.0 -= absorbed;
+                    amount -= absorbed;
+                }
+
+                enemy.health -= amount;
+                (transform, amount, enemy.health)
+            } else if let Ok((transform, mut pawn, invincible)) = player.get_mut(event.target) {
+                if pawn.health <= 0. || invincible {
+                    continue;
+                }
+
+                // Armor only stops physical hits
+                let amount = match event.damage_type {
+                    DamageType::Physical => mitigate(pawn.armor, event.amount),
+                    _ => event.amount,
+                };
+
+                pawn.health -= amount;
+                (transform, amount, pawn.health)
+            } else {
+                continue;
+            };
+
+        dealt_events.send(DamageDealt {
+            target: event.target,
+            position: transform.translation.truncate(),
+            amount,
+            crit: event.crit,
+            source: event.source,
+            damage_type: event.damage_type,
+        });
+        if health <= 0. {
+            death_events.send(DeathEvent {
+                entity: event.target,
+                killer: event.source,
+                damage_type: event.damage_type,
+            });
+        }
+    }
+}
+
+fn mitigate(armor: f32, amount: f32) -> f32 {
+    (amount - armor).max(MIN_DAMAGE.min(amount))
+}
+
+fn setup_damage_numbers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pool: ResMut<DamageNumberPool>,
+) {
+    let font = asset_server.load("fonts/quaver.ttf");
+
+    pool.next = 0;
+    pool.entities = (0..DAMAGE_NUMBER_POOL_SIZE)
+        .map(|_| {
+            let mut lifetime = Timer::from_seconds(DAMAGE_NUMBER_LIFETIME, TimerMode::Once);
+            lifetime.pause();
+
+            commands
+                .spawn((
+                    Text2dBundle {
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: DAMAGE_NUMBER_SIZE,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    DamageNumber { lifetime },
+                ))
+                .id()
+        })
+        .collect();
+}
+
+fn toggle_damage_numbers(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard_input.just_pressed(DAMAGE_NUMBERS_KEY) {
+        settings.damage_numbers = !settings.damage_numbers;
+    }
+}
+
+fn show_damage_numbers(
+    settings: Res<Settings>,
+    mut events: EventReader<DamageDealt>,
+    mut pool: ResMut<DamageNumberPool>,
+    player: Query<(), With<Pawn>>,
+    mut numbers: Query<(
+        &mut Text,
+        &mut Transform,
+        &mut Visibility,
+        &mut DamageNumber,
+    )>,
+) {
+    if !settings.damage_numbers || pool.entities.is_empty() {
+        events.clear();
+        return;
+    }
+
+    for event in events.read() {
+        // Hits a shield soaked up entirely have nothing to show
+        if player.contains(event.target) || event.amount <= 0. {
+            continue;
+        }
+
+        // When every number is in flight the oldest one gets reused
+        let entity = pool.entities[pool.next];
+        pool.next = (pool.next + 1) % pool.entities.len();
+        let Ok((mut text, mut transform, mut visibility, mut number)) = numbers.get_mut(entity)
+        else {
+            continue;
+        };
+
+        let jitter = Vec2::new(fastrand::f32() - 0.5, fastrand::f32()) * 8.;
+        transform.translation = (event.position + jitter).extend(10.);
+
+        let section = &mut text.sections[0];
+        section.value = format!("{:.0}", event.amount);
+        if event.crit {
+            section.value.push('!');
+            section.style.color = CRIT_COLOR;
+            section.style.font_size = CRIT_NUMBER_SIZE;
+        } else {
+            section.style.color = event.damage_type.color();
+            section.style.font_size = DAMAGE_NUMBER_SIZE;
+        }
+
+        *visibility = Visibility::Visible;
+        number.lifetime.reset();
+        number.lifetime.unpause();
+    }
+}
+
+fn animate_damage_numbers(
+    time: Res<Time>,
+    mut numbers: Query<(
+        &mut Text,
+        &mut Transform,
+        &mut Visibility,
+        &mut DamageNumber,
+    )>,
+) {
+    for (mut text, mut transform, mut visibility, mut number) in &mut numbers {
+        if number.lifetime.paused() {
+            continue;
+        }
+
+        if number.lifetime.tick(time.delta()).finished() {
+            number.lifetime.pause();
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        transform.translation.y += DAMAGE_NUMBER_RISE * time.delta_seconds();
+        text.sections[0]
+            .style
+            .color
+            .set_a(1. - number.lifetime.fraction());
+    }
+}
+
+fn cleanup_damage_numbers(
+    mut commands: Commands,
+    mut pool: ResMut<DamageNumberPool>,
+    query: Query<Entity, With<DamageNumber>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+    pool.entities.clear();
+}
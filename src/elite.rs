@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+
+use crate::boss::Boss;
+use crate::components::Enemy;
+use crate::enemy::EnemySprite;
+use crate::{AppState, RunTime};
+
+const BASE_ELITE_CHANCE: f32 = 0.02;
+const ELITE_CHANCE_PER_MINUTE: f32 = 0.01;
+const MAX_ELITE_CHANCE: f32 = 0.25;
+// One more affix is rolled for every this many minutes survived
+const MINUTES_PER_AFFIX: f32 = 5.;
+const MAX_AFFIXES: usize = 3;
+
+const ELITE_SCALE: f32 = 1.25;
+const ELITE_OUTLINE_COLOR: Color = Color::GOLD;
+
+pub struct ElitePlugin;
+
+impl Plugin for ElitePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (promote_elites, regenerate).run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(
+            Update,
+            draw_elite_outlines.run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Affix {
+    Tough,
+    Shielded,
+    Fast,
+    Regenerating,
+    Explosive,
+}
+
+impl Affix {
+    const ALL: [Affix; 5] = [
+        Affix::Tough,
+        Affix::Shielded,
+        Affix::Fast,
+        Affix::Regenerating,
+        Affix::Explosive,
+    ];
+
+    fn tint(self) -> Color {
+        match self {
+            Affix::Tough => Color::rgb(1., 0.55, 0.55),
+            Affix::Shielded => Color::rgb(0.55, 0.75, 1.),
+            Affix::Fast => Color::rgb(1., 1., 0.5),
+            Affix::Regenerating => Color::rgb(0.55, 1., 0.55),
+            Affix::Explosive => Color::rgb(1., 0.7, 0.3),
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Elite {
+    pub affixes: Vec<Affix>,
+    pub tint: Color,
+}
+
+// Absorbs damage before it reaches the enemy's health
+#[derive(Component, Debug)]
+pub struct Shield(pub f32);
+
+#[derive(Component, Debug)]
+pub struct Regenerating {
+    pub per_second: f32,
+    pub max_health: f32,
+}
+
+#[derive(Component, Debug)]
+pub struct Explosive {
+    pub radius: f32,
+    pub damage: f32,
+}
+
+fn promote_elites(
+    mut commands: Commands,
+    run_time: Res<RunTime>,
+    mut enemies: Query<
+        (Entity, &mut EnemySprite, &mut Sprite, &mut Transform),
+        (Added<Enemy>, Without<Boss>),
+    >,
+) {
+    let minutes = run_time.elapsed_secs() / 60.;
+    let chance = (BASE_ELITE_CHANCE + ELITE_CHANCE_PER_MINUTE * minutes).min(MAX_ELITE_CHANCE);
+    let affix_count = (1 + (minutes / MINUTES_PER_AFFIX) as usize).min(MAX_AFFIXES);
+
+    for (entity, mut enemy, mut sprite, mut transform) in &mut enemies {
+        if fastrand::f32() >= chance {
+            continue;
+        }
+
+        let mut pool = Affix::ALL.to_vec();
+        fastrand::shuffle(&mut pool);
+        let affixes: Vec<Affix> = pool.into_iter().take(affix_count).collect();
+
+        // Stat changes go first so the other affixes scale off the final health
+        if affixes.contains(&Affix::Tough) {
+            enemy.health *= 3.;
+        }
+        if affixes.contains(&Affix::Fast) {
+            enemy.speed *= 1.5;
+        }
+
+        let mut entity_commands = commands.entity(entity);
+        if affixes.contains(&Affix::Shielded) {
+            entity_commands.insert(Shield(enemy.health * 0.5));
+        }
+        if affixes.contains(&Affix::Regenerating) {
+            entity_commands.insert(Regenerating {
+                per_second: enemy.health * 0.05,
+                max_health: enemy.health,
+            });
+        }
+        if affixes.contains(&Affix::Explosive) {
+            entity_commands.insert(Explosive {
+                radius: enemy.width * 3.,
+                damage: 10.,
+            });
+        }
+
+        enemy.score *= 2. * (1 + affixes.len()) as f32;
+
+        let tint = affixes[0].tint();
+        sprite.color = tint;
+        transform.scale *= ELITE_SCALE;
+
+        entity_commands.insert(Elite { affixes, tint });
+    }
+}
+
+fn regenerate(time: Res<Time>, mut enemies: Query<(&mut EnemySprite, &Regenerating)>) {
+    for (mut enemy, regenerating) in &mut enemies {
+        enemy.health = (enemy.health + regenerating.per_second * time.delta_seconds())
+            .min(regenerating.max_health);
+    }
+}
+
+fn draw_elite_outlines(mut gizmos: Gizmos, elites: Query<(&Transform, &EnemySprite), With<Elite>>) {
+    for (transform, enemy) in &elites {
+        let radius = enemy.width.max(enemy.height) / 2. * transform.scale.x;
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            radius,
+            ELITE_OUTLINE_COLOR,
+        );
+    }
+}
@@ -1,201 +1,374 @@
-use crate::animation::{AnimationIndices, AnimationTimer};
-use crate::collision::{Collided, EnemyHitPlayer, EnemyHitWeapon};
+use crate::animation::{
+    AnimationClip, AnimationController, AnimationState, ClipName, DespawnOnFinish,
+};
+use crate::behavior::BehaviorState;
+use crate::boss::{AreaAttack, Boss};
+use crate::collision::{EnemyHitWeapon, WeaponContacts};
 use crate::components::*;
 use crate::constants::*;
+use crate::damage::{DamageDealt, DamageEvent, DamageType, DeathEvent};
+use crate::elite::{Elite, Explosive};
+use crate::game_time::GameTime;
+use crate::loot::LootDrop;
 use crate::pawn::Attack;
 use crate::settings::Settings;
+use crate::spatial::{EnemyGrid, SpatialSet};
+use crate::spawning::{SpawnPlacement, SpawnSide};
+use crate::status::{ApplyStatus, StatusApplication, StatusEffects, StatusKind};
+use crate::steering::{steering_direction, Neighbor, SteeringWeights};
+use crate::weapon::{Hitbox, Weapon};
 use crate::AppState;
 use crate::MyCollisionEvent;
 use crate::{ScoreEvent, Scoreboard};
+use bevy::asset::ron;
 use bevy::audio::{AudioBundle, PlaybackMode, PlaybackSettings, Volume};
 use bevy::prelude::*;
 // use bevy_kira_audio::prelude::*;
 use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
 
-const IDLE_ANIMATION: AnimationIndices = AnimationIndices { first: 0, last: 1 };
-const RUN_ANIMATION: AnimationIndices = AnimationIndices { first: 0, last: 1 };
+const IDLE_ANIMATION: AnimationClip = AnimationClip::new(0, 1, 0.1);
+const RUN_ANIMATION: AnimationClip = AnimationClip::new(0, 1, 0.1);
+const EXPLOSION_FUSE: f32 = 0.5;
+const DEFAULT_CONTACT_RATE: f32 = 1.;
+// Share of regular spawns placed in the direction the pawn is moving
+const AHEAD_SPAWN_CHANCE: f32 = 0.3;
+// Real seconds the game freezes for when a boss or elite goes down
+const BOSS_HIT_STOP: f32 = 0.25;
+const ELITE_HIT_STOP: f32 = 0.08;
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            FixedUpdate,
-            (
-                spawn_enemies,
-                move_enemies,
-                collided_with_weapon,
-                collided_with_player,
+        app.add_event::<SpawnEnemy>()
+            .add_systems(Startup, load_enemy_definitions)
+            .configure_sets(
+                FixedUpdate,
+                (EnemySet::Steering, EnemySet::Damage, EnemySet::Death)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
             )
-                .run_if(in_state(AppState::InGame)),
-        )
-        .add_systems(OnExit(AppState::InGame), cleanup_sprites);
+            .add_systems(
+                FixedUpdate,
+                (
+                    spawn_enemies.after(SpatialSet).before(EnemySet::Steering),
+                    tick_hit_stun.before(EnemySet::Steering),
+                    move_enemies.in_set(EnemySet::Steering),
+                    collided_with_weapon.in_set(EnemySet::Damage),
+                    (
+                        score_weapon_hits,
+                        hit_stop_on_big_kills,
+                        handle_enemy_deaths,
+                        spawn_requested_enemies,
+                    )
+                        .chain()
+                        .in_set(EnemySet::Death),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_sprites);
     }
 }
 
-#[derive(Component, Clone, Debug)]
+// Per-tick ordering for enemies: pick velocities, take damage, then resolve deaths
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EnemySet {
+    Steering,
+    Damage,
+    Death,
+}
+
+#[derive(Component, Clone, Debug, Deserialize)]
 pub struct EnemySprite {
     filename: String,
-    layout: TextureAtlasLayout,
-    idle: AnimationIndices,
-    run: AnimationIndices,
-    speed: f32,
+    columns: usize,
+    rows: usize,
+    #[serde(default)]
+    padding: Option<(f32, f32)>,
+    idle: AnimationClip,
+    run: AnimationClip,
+    // Left unset for sheets that only have a walk cycle, which then keep playing idle or run
+    #[serde(default)]
+    hit: Option<AnimationClip>,
+    #[serde(default)]
+    death: Option<AnimationClip>,
+    #[serde(default)]
+    attack: Option<AnimationClip>,
+    // World units per second
+    pub speed: f32,
     pub height: f32,
     pub width: f32,
-    health: f32,
-    score: f32,
+    pub health: f32,
+    pub score: f32,
+    pub contact_damage: f32,
+    // Hits per second while touching the pawn
+    pub contact_rate: f32,
+    #[serde(default)]
+    pub contact_status: Option<StatusApplication>,
+    // 0 takes full knockback and stun, 1 ignores them
+    #[serde(default)]
+    pub knockback_resistance: f32,
+    #[serde(default)]
+    steering: SteeringWeights,
+    #[serde(default)]
+    pub behavior: EnemyBehavior,
+    #[serde(default)]
+    pub immunities: Vec<StatusKind>,
+    // Fraction of each damage type ignored, 1 being full immunity
+    #[serde(default)]
+    pub resistances: HashMap<DamageType, f32>,
+    #[serde(default)]
+    pub loot: Vec<LootDrop>,
+}
+
+impl EnemySprite {
+    fn layout(&self) -> TextureAtlasLayout {
+        TextureAtlasLayout::from_grid(
+            Vec2::new(self.width, self.height),
+            self.columns,
+            self.rows,
+            self.padding.map(|(x, y)| Vec2::new(x, y)),
+            None,
+        )
+    }
+
+    pub fn animation(&self) -> AnimationController {
+        let base = AnimationController::new(self.idle).with_clip(ClipName::Run, self.run);
+        [
+            (ClipName::Hit, self.hit),
+            (ClipName::Death, self.death),
+            (ClipName::Attack, self.attack),
+        ]
+        .into_iter()
+        .filter_map(|(name, clip)| Some((name, clip?)))
+        .fold(base, |controller, (name, clip)| {
+            controller.with_clip(name, clip)
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub enum EnemyBehavior {
+    #[default]
+    Chaser,
+    // Stops to telegraph, then dashes along a locked-in direction
+    Charger {
+        range: f32,
+        telegraph: f32,
+        dash_speed: f32,
+        dash_duration: f32,
+        cooldown: f32,
+    },
+    // Holds position at a distance and fires projectiles at the pawn
+    Ranged {
+        preferred_distance: f32,
+        range: f32,
+        cooldown: f32,
+        projectile_speed: f32,
+        projectile_damage: f32,
+        #[serde(default)]
+        projectile_status: Option<StatusApplication>,
+    },
+    // Breaks into `count` copies of the `into` enemy when killed
+    Splitter {
+        into: String,
+        count: usize,
+    },
+    // Blinks next to the pawn when it gets too far away
+    Teleporter {
+        trigger_distance: f32,
+        blink_distance: f32,
+        cooldown: f32,
+    },
+}
+
+#[derive(Resource, Default, Deref)]
+pub struct EnemyDefinitions(HashMap<String, EnemySprite>);
+
+#[derive(Event)]
+pub struct SpawnEnemy {
+    pub name: String,
+    pub position: Vec3,
 }
 
 #[derive(Bundle)]
 pub struct EnemyBundle {
     pub sprite: SpriteSheetBundle,
-    pub animation_indices: AnimationIndices,
-    pub animation_timer: AnimationTimer,
+    pub animation: AnimationController,
+    pub animation_state: AnimationState,
     pub pawn: Enemy,
     pub sprite_details: EnemySprite,
+    pub contact_cooldown: ContactCooldown,
+    pub hit_cooldowns: HitCooldowns,
+    pub status_effects: StatusEffects,
 }
 
+// Enemies stop steering while stunned so knockback can play out
+#[derive(Component, Deref, DerefMut)]
+pub struct HitStun(pub Timer);
+
+// Time until each hitbox can hit this enemy again, keyed by hitbox entity
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct HitCooldowns(pub HashMap<Entity, Timer>);
+
+// Time until this enemy can hurt the pawn again by touching it
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct ContactCooldown(pub Timer);
+
 impl Default for EnemyBundle {
     fn default() -> Self {
         EnemyBundle {
             sprite: SpriteSheetBundle::default(),
-            animation_indices: IDLE_ANIMATION,
-            animation_timer: AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+            animation: AnimationController::new(IDLE_ANIMATION),
+            animation_state: AnimationState::Idle,
             pawn: Enemy,
             sprite_details: EnemySprite {
                 filename: "16x32.png".to_string(),
-                layout: TextureAtlasLayout::new_empty(Vec2::ZERO),
+                columns: 1,
+                rows: 1,
+                padding: None,
                 idle: IDLE_ANIMATION,
                 run: RUN_ANIMATION,
+                hit: None,
+                death: None,
+                attack: None,
                 height: 16.,
                 width: 16.,
-                speed: 0.1,
+                speed: 6.,
                 health: 1.,
                 score: 1.,
+                contact_damage: 1.,
+                contact_rate: 1.,
+                contact_status: None,
+                knockback_resistance: 0.,
+                steering: SteeringWeights::default(),
+                behavior: EnemyBehavior::Chaser,
+                immunities: Vec::new(),
+                resistances: HashMap::new(),
+                loot: Vec::new(),
             },
+            contact_cooldown: ContactCooldown::default(),
+            hit_cooldowns: HitCooldowns::default(),
+            status_effects: StatusEffects::default(),
         }
     }
 }
 
-fn find_good_spot(
-    _enemies: Query<&Transform, With<Enemy>>,
-    player: Query<&Transform, With<Pawn>>,
-) -> Vec3 {
-    let player_pos = player.single().translation;
-    let distance_x = (player_pos.x + WIDTH / 2.).trunc() as usize;
-    let distance_y = (player_pos.y + HEIGHT / 2.).trunc() as usize;
-
-    let mut x = fastrand::usize(..distance_x) as isize;
-    let mut y = fastrand::usize(..distance_y) as isize;
+fn load_enemy_definitions(mut commands: Commands) {
+    let mut definitions: HashMap<String, EnemySprite> =
+        ron::from_str(include_str!("../assets/data/enemies.ron"))
+            .expect("Failed to parse enemy definitions");
 
-    if fastrand::bool() {
-        x = -x;
-    }
-    if fastrand::bool() {
-        y = -y;
+    for (name, definition) in &mut definitions {
+        // Contact cooldowns last 1 / contact_rate seconds
+        if definition.contact_rate <= 0. {
+            warn!(
+                "Enemy {name} has contact_rate {}, using {DEFAULT_CONTACT_RATE}",
+                definition.contact_rate
+            );
+            definition.contact_rate = DEFAULT_CONTACT_RATE;
+        }
     }
 
-    let enemy_transform = Transform::from_translation(Vec3::new(x as f32, y as f32, 0.));
-    if enemy_transform.translation.distance(player_pos) < WIDTH / 2. {
-        return find_good_spot(_enemies, player);
-    }
-    Vec3::new(x as f32, y as f32, 2.)
+    commands.insert_resource(EnemyDefinitions(definitions));
 }
 
-fn green_kobold() -> EnemySprite {
-    EnemySprite {
-        filename: String::from("enemies/green_kobold.png"),
-        layout: TextureAtlasLayout::from_grid(Vec2::new(16., 20.), 8, 1, None, None),
-        idle: AnimationIndices { first: 0, last: 1 },
-        run: AnimationIndices { first: 0, last: 7 },
-        width: 16.,
-        height: 20.,
-        speed: 0.3,
-        health: 1000.,
-        score: 100.,
+pub fn spawn_enemies(
+    grid: Res<EnemyGrid>,
+    placement: SpawnPlacement,
+    scoreboard: Res<Scoreboard>,
+    mut spawn_events: EventWriter<SpawnEnemy>,
+) {
+    if grid.len() >= ((scoreboard.kills + 1) * 2) as usize {
+        return;
     }
-}
 
-fn blue_kobold() -> EnemySprite {
-    EnemySprite {
-        filename: String::from("enemies/blue_kobold.png"),
-        layout: TextureAtlasLayout::from_grid(Vec2::new(16., 20.), 8, 1, None, None),
-        idle: AnimationIndices { first: 0, last: 1 },
-        run: AnimationIndices { first: 0, last: 7 },
-        width: 16.,
-        height: 20.,
-        speed: 0.3,
-        health: 2000.,
-        score: 200.,
-    }
+    let side = if fastrand::f32() < AHEAD_SPAWN_CHANCE {
+        SpawnSide::Ahead
+    } else {
+        SpawnSide::Around
+    };
+    // Every candidate was blocked, so try again next tick
+    let Some(position) = placement.find(side) else {
+        return;
+    };
+
+    let name = match scoreboard.kills {
+        0..=25 => "green_kobold",
+        26..=50 => pick(&["green_kobold", "green_kobold", "spiky_kobold"]),
+        51..=75 => pick(&["blue_kobold", "spiky_kobold", "skelly", "blue_knight"]),
+        76..=100 => pick(&["troll", "skelly", "blue_knight"]),
+        _ => pick(&["green_kobold", "blue_kobold", "spiky_kobold", "skelly"]),
+    };
+
+    spawn_events.send(SpawnEnemy {
+        name: name.to_string(),
+        position,
+    });
 }
 
-fn troll() -> EnemySprite {
-    EnemySprite {
-        filename: String::from("enemies/troll.png"),
-        layout: TextureAtlasLayout::from_grid(
-            Vec2::new(48., 38.),
-            12,
-            1,
-            Some(Vec2::new(16., 0.)),
-            None,
-        ),
-        idle: AnimationIndices { first: 0, last: 1 },
-        run: AnimationIndices { first: 0, last: 11 },
-        width: 48.,
-        height: 38.,
-        speed: 0.1,
-        health: 5000.,
-        score: 1000.,
-    }
+fn pick<'a>(names: &[&'a str]) -> &'a str {
+    names[fastrand::usize(..names.len())]
 }
 
-pub fn spawn_enemies(
+fn spawn_requested_enemies(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    enemies: Query<&Transform, With<Enemy>>,
-    player: Query<&Transform, With<Pawn>>,
-    scoreboard: Res<Scoreboard>,
+    definitions: Res<EnemyDefinitions>,
+    mut spawn_events: EventReader<SpawnEnemy>,
 ) {
-    let count = enemies.iter().count();
-
-    let good_spot = find_good_spot(enemies, player);
-
-    if count < ((scoreboard.kills + 1) * 2) as usize {
-        let enemy = match scoreboard.kills {
-            0..=50 => green_kobold(),
-            51..=75 => blue_kobold(),
-            76..=100 => troll(),
-            _ => green_kobold(),
+    for SpawnEnemy { name, position } in spawn_events.read() {
+        let Some(enemy) = definitions.get(name) else {
+            warn!("Unknown enemy definition: {}", name);
+            continue;
         };
 
-        let texture: Handle<Image> = asset_server.load(&enemy.filename);
-        let layout = enemy.layout.clone();
-        let animation_indices = AnimationIndices {
-            first: enemy.idle.first,
-            last: enemy.idle.last,
-        };
-        let mut transform = Transform::from_translation(good_spot);
-        transform = transform.with_scale(Vec3::splat(1.));
+        spawn_enemy(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlas_layouts,
+            enemy,
+            *position,
+        );
+    }
+}
+
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+    enemy: &EnemySprite,
+    position: Vec3,
+) -> Entity {
+    let texture: Handle<Image> = asset_server.load(&enemy.filename);
+    let layout = enemy.layout();
+    let mut transform = Transform::from_translation(position);
+    transform = transform.with_scale(Vec3::splat(1.));
 
-        commands.spawn((
+    commands
+        .spawn((
             EnemyBundle {
                 sprite: SpriteSheetBundle {
                     texture,
                     transform, // Controls the placement of the sprite
                     atlas: TextureAtlas {
                         layout: texture_atlas_layouts.add(layout),
-                        index: animation_indices.first,
+                        index: enemy.idle.first,
                     },
                     ..default()
                 },
-                animation_indices,
+                animation: enemy.animation(),
                 pawn: Enemy,
                 sprite_details: enemy.clone(),
                 ..default()
             },
+            BehaviorState::default(),
             RigidBody::Dynamic,
+            Velocity::zero(),
+            ExternalImpulse::default(),
             LockedAxes::ROTATION_LOCKED,
             Collider::cuboid(enemy.width / 2., enemy.height / 2.),
             Damping {
@@ -212,94 +385,249 @@ pub fn spawn_enemies(
             },
             AdditionalMassProperties::Mass(1.),
             SolverGroups::new(ENEMY_WEAPON_GROUP, Group::default()),
-        ));
-    }
+        ))
+        .id()
 }
 
 pub fn move_enemies(
-    mut params: ParamSet<(
-        Query<&Transform, With<Pawn>>,
-        Query<
-            (
-                &mut Transform,
-                &mut AnimationIndices,
-                &mut TextureAtlas,
-                &mut Sprite,
-                &EnemySprite,
-            ),
-            With<Enemy>,
-        >,
-    )>,
+    grid: Res<EnemyGrid>,
+    player: Query<&Transform, (With<Pawn>, Without<Enemy>)>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Velocity,
+            &mut AnimationState,
+            &mut Sprite,
+            &EnemySprite,
+        ),
+        (With<Enemy>, Without<HitStun>),
+    >,
 ) {
-    let player_pos = params.p0().single().translation;
-    for (mut transform, mut animation_indices, mut atlas, mut sprite, sprite_details) in
-        &mut params.p1()
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+
+    for (entity, transform, mut velocity, mut animation_state, mut sprite, sprite_details) in
+        &mut enemies
     {
-        let mut direction = player_pos - transform.translation;
-        direction = direction.normalize();
-        sprite.flip_x = direction.x < 0.;
-        transform.translation += direction * sprite_details.speed;
-
-        let new_animation_indices = AnimationIndices {
-            first: sprite_details.run.first,
-            last: sprite_details.run.last,
-        };
+        let position = transform.translation.truncate();
+        let steering = &sprite_details.steering;
+        let radius = steering.separation_radius.max(steering.alignment_radius);
+        let neighbors = grid.within(position, radius).map(|entry| Neighbor {
+            entity: entry.entity,
+            position: entry.position,
+            velocity: entry.velocity,
+        });
+        let direction = steering_direction(steering, entity, position, player_pos, neighbors);
+        if direction.x != 0. {
+            sprite.flip_x = direction.x < 0.;
+        }
+        velocity.linvel = direction * sprite_details.speed;
 
-        animation_indices.first = new_animation_indices.first;
-        animation_indices.last = new_animation_indices.last;
-        if atlas.index > animation_indices.last || atlas.index < animation_indices.first {
-            atlas.index = animation_indices.first;
+        let moving = if direction == Vec2::ZERO {
+            AnimationState::Idle
+        } else {
+            AnimationState::Moving
+        };
+        if *animation_state != moving {
+            *animation_state = moving;
         }
     }
 }
 
-fn collided_with_player(
+#[allow(clippy::too_many_arguments)]
+fn collided_with_weapon(
     mut commands: Commands,
-    mut collision_events: EventReader<EnemyHitPlayer>,
-    mut enemies: Query<(Entity, &mut EnemySprite), With<Enemy>>,
+    attack: Res<Attack>,
+    mut status_events: EventWriter<ApplyStatus>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut contacts: ResMut<WeaponContacts>,
+    hitboxes: Query<(&Hitbox, &Transform), Without<Enemy>>,
+    weapons: Query<&Weapon>,
+    mut enemies: Query<(
+        &Transform,
+        &EnemySprite,
+        &mut HitCooldowns,
+        &mut ExternalImpulse,
+    )>,
+    time: Res<Time>,
 ) {
-    if enemies.is_empty() {
-        return;
+    for (_, _, mut cooldowns, ..) in &mut enemies {
+        cooldowns.retain(|_, timer| !timer.tick(time.delta()).finished());
     }
 
-    for collision_event in collision_events.read() {
-        match enemies.get_mut(collision_event.0) {
-            Err(_) => continue,
-            Ok((_, mut enemy)) => {
-                enemy.health -= 1.;
-                if enemy.health <= 0. {
-                    commands.get_entity(collision_event.0).unwrap().despawn();
-                }
-            }
+    contacts.retain(|(hitbox, enemy)| hitboxes.contains(*hitbox) && enemies.contains(*enemy));
+
+    for &(hitbox_entity, entity) in contacts.iter() {
+        let Ok((&Hitbox(weapon_entity), hitbox_transform)) = hitboxes.get(hitbox_entity) else {
+            continue;
+        };
+        let Ok(weapon) = weapons.get(weapon_entity) else {
+            continue;
         };
+        let Ok((transform, enemy, mut cooldowns, mut impulse)) = enemies.get_mut(entity) else {
+            continue;
+        };
+        if cooldowns.contains_key(&hitbox_entity) {
+            continue;
+        }
+        cooldowns.insert(
+            hitbox_entity,
+            Timer::from_seconds(weapon.hit_interval(&attack), TimerMode::Once),
+        );
+
+        let susceptibility = 1. - enemy.knockback_resistance.clamp(0., 1.);
+        if susceptibility > 0. {
+            let away = (transform.translation - hitbox_transform.translation)
+                .truncate()
+                .normalize_or_zero();
+            impulse.impulse += away * weapon.knockback * susceptibility;
+            if weapon.stun > 0. {
+                commands.entity(entity).insert(HitStun(Timer::from_seconds(
+                    weapon.stun * susceptibility,
+                    TimerMode::Once,
+                )));
+            }
+        }
+
+        if let Some(application) = weapon.status(&attack) {
+            status_events.send(ApplyStatus {
+                target: entity,
+                application,
+                source: Some(weapon_entity),
+            });
+        }
+
+        let crit = fastrand::f32() < attack.crit_chance;
+        let mut damage = weapon.damage(&attack);
+        if crit {
+            damage *= attack.crit_multiplier;
+        }
+        damage_events.send(DamageEvent {
+            target: entity,
+            amount: damage,
+            crit,
+            source: Some(weapon_entity),
+            damage_type: DamageType::Physical,
+        });
     }
 }
 
-fn collided_with_weapon(
+// Weapon hits that don't kill are worth a few points on their own
+fn score_weapon_hits(
+    mut dealt_events: EventReader<DamageDealt>,
+    mut score_events: EventWriter<ScoreEvent>,
+    weapons: Query<(), With<Weapon>>,
+    enemies: Query<&EnemySprite>,
+) {
+    for hit in dealt_events.read() {
+        let from_weapon = hit.source.is_some_and(|source| weapons.contains(source));
+        let survived = enemies.get(hit.target).is_ok_and(|enemy| enemy.health > 0.);
+        if from_weapon && survived {
+            score_events.send(ScoreEvent::EnemyHit);
+        }
+    }
+}
+
+// Anything that still needs to look at a dead enemy should run before this
+fn hit_stop_on_big_kills(
+    mut game_time: ResMut<GameTime>,
+    mut deaths: EventReader<DeathEvent>,
+    enemies: Query<(Has<Boss>, Has<Elite>), With<Enemy>>,
+) {
+    for death in deaths.read() {
+        match enemies.get(death.entity) {
+            Ok((true, _)) => game_time.hit_stop(BOSS_HIT_STOP),
+            Ok((_, true)) => game_time.hit_stop(ELITE_HIT_STOP),
+            _ => {}
+        }
+    }
+}
+
+pub fn handle_enemy_deaths(
     mut commands: Commands,
-    attack: Res<Attack>,
     asset_server: Res<AssetServer>,
-    mut score_events: EventWriter<ScoreEvent>,
-    mut collided_enemies: Query<(Entity, &mut EnemySprite), With<Collided>>,
-    mut time: ResMut<Time>,
     settings: Res<Settings>,
+    mut deaths: EventReader<DeathEvent>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut spawn_events: EventWriter<SpawnEnemy>,
+    enemies: Query<
+        (
+            &Transform,
+            &EnemySprite,
+            Option<&Explosive>,
+            &Sprite,
+            &Handle<Image>,
+            &TextureAtlas,
+            &AnimationController,
+        ),
+        With<Enemy>,
+    >,
 ) {
-    for (entity, mut enemy) in &mut collided_enemies {
-        enemy.health -= attack.damage_amount * attack.damage_scale;
-        if enemy.health <= 0. {
-            commands.entity(entity).despawn();
-            score_events.send(ScoreEvent::Scored(enemy.score as u32));
-            let sfx = asset_server.load("sfx/enemy_death.ogg");
-            commands.spawn(AudioBundle {
-                source: sfx,
-                settings: PlaybackSettings {
-                    volume: Volume::new(settings.volume),
-                    mode: PlaybackMode::Once,
+    for &DeathEvent { entity, .. } in deaths.read() {
+        let Ok((transform, enemy, explosive, sprite, texture, atlas, animation)) =
+            enemies.get(entity)
+        else {
+            continue;
+        };
+
+        commands.entity(entity).despawn();
+        // The enemy is gone at once, a stand-in with no gameplay plays out its death
+        if animation.has(ClipName::Death) {
+            let mut corpse = animation.clone();
+            corpse.play(ClipName::Death);
+            commands.spawn((
+                SpriteSheetBundle {
+                    sprite: sprite.clone(),
+                    texture: texture.clone(),
+                    atlas: atlas.clone(),
+                    transform: *transform,
                     ..default()
                 },
-            });
-        } else {
-            score_events.send(ScoreEvent::EnemyHit);
+                corpse,
+                DespawnOnFinish,
+            ));
+        }
+        if let Some(explosive) = explosive {
+            commands.spawn((
+                TransformBundle::from_transform(Transform::from_translation(transform.translation)),
+                AreaAttack::new(explosive.radius, explosive.damage, EXPLOSION_FUSE)
+                    .with_source(entity, DamageType::Fire)
+                    .hurting_enemies(),
+            ));
+        }
+        if let EnemyBehavior::Splitter { into, count } = &enemy.behavior {
+            for _ in 0..*count {
+                let offset = Vec2::from_angle(fastrand::f32() * std::f32::consts::TAU) * 8.;
+                spawn_events.send(SpawnEnemy {
+                    name: into.clone(),
+                    position: transform.translation + offset.extend(0.),
+                });
+            }
+        }
+        score_events.send(ScoreEvent::Scored(enemy.score as u32));
+        let sfx = asset_server.load("sfx/enemy_death.ogg");
+        commands.spawn(AudioBundle {
+            source: sfx,
+            settings: PlaybackSettings {
+                volume: Volume::new(settings.volume),
+                mode: PlaybackMode::Once,
+                ..default()
+            },
+        });
+    }
+}
+
+fn tick_hit_stun(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut stunned: Query<(Entity, &mut HitStun)>,
+) {
+    for (entity, mut stun) in &mut stunned {
+        if stun.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<HitStun>();
         }
     }
 }
@@ -309,8 +637,3 @@ fn cleanup_sprites(mut commands: Commands, query: Query<Entity, With<Enemy>>) {
         commands.entity(entity).despawn();
     }
 }
-
-fn idle() {}
-fn run() {}
-fn play_idle_animation() {}
-fn play_run_animation() {}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::AppState;
+
+// Game systems read `Res<Time>`, which is virtual time in Update and fixed time in
+// FixedUpdate. Both follow `Time<Virtual>`, and so does physics since rapier steps in
+// FixedUpdate, so driving that one clock pauses or rescales the whole simulation.
+// Any pause reason stops it outright, otherwise it runs at the slowest active
+// slow-motion speed, with hit-stop being slow motion at zero speed.
+// Menus and other UI read `Time<Real>` instead and keep running.
+pub struct GameTimePlugin;
+
+impl Plugin for GameTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameTime>()
+            // Last, so every request made this frame lands before the next one starts
+            .add_systems(Last, apply_game_time)
+            .add_systems(OnExit(AppState::InGame), reset_game_time);
+    }
+}
+
+// Anything that stops the simulation, kept apart so one can't undo another
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PauseReason {
+    LevelUp,
+    Chest,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TimeEffect {
+    speed: f32,
+    // Real seconds left
+    remaining: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct GameTime {
+    paused: HashSet<PauseReason>,
+    effects: Vec<TimeEffect>,
+}
+
+impl GameTime {
+    pub fn pause(&mut self, reason: PauseReason) {
+        self.paused.insert(reason);
+    }
+
+    pub fn resume(&mut self, reason: PauseReason) {
+        self.paused.remove(&reason);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        !self.paused.is_empty()
+    }
+
+    // Runs the simulation at `speed` for `duration` real seconds, the slowest effect wins
+    pub fn slow_motion(&mut self, speed: f32, duration: f32) {
+        self.effects.push(TimeEffect {
+            speed: speed.max(0.),
+            remaining: duration,
+        });
+    }
+
+    // A brief freeze that sells the weight of a big hit
+    pub fn hit_stop(&mut self, duration: f32) {
+        self.slow_motion(0., duration);
+    }
+
+    pub fn is_slowed(&self) -> bool {
+        !self.effects.is_empty()
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|effect| effect.speed)
+            .fold(1., f32::min)
+    }
+
+    fn tick(&mut self, delta: f32) {
+        for effect in &mut self.effects {
+            effect.remaining -= delta;
+        }
+        self.effects.retain(|effect| effect.remaining > 0.);
+    }
+}
+
+fn apply_game_time(
+    real_time: Res<Time<Real>>,
+    mut game_time: ResMut<GameTime>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    game_time.tick(real_time.delta_seconds());
+
+    let speed = game_time.speed();
+    // Zero speed is a pause, so timers see no time pass at all
+    if game_time.is_paused() || speed <= 0. {
+        virtual_time.pause();
+    } else {
+        virtual_time.unpause();
+        if virtual_time.relative_speed() != speed {
+            virtual_time.set_relative_speed(speed);
+        }
+    }
+}
+
+// Nothing carries over from one run to the next
+fn reset_game_time(mut game_time: ResMut<GameTime>) {
+    *game_time = GameTime::default();
+}
@@ -0,0 +1,434 @@
+use bevy::prelude::*;
+
+use crate::constants::*;
+use crate::progression::Experience;
+use crate::run::format_run_time;
+use crate::spatial::EnemyGrid;
+use crate::weapon::{weapon_sheet_layout, PassiveDefinitions, Passives, Weapon, WeaponDefinitions};
+use crate::{AppState, RunTime, Scoreboard};
+
+const EXPERIENCE_BAR_HEIGHT: f32 = 12.;
+const ICON_SIZE: f32 = 28.;
+const ICON_GAP: f32 = 4.;
+const HUD_MARGIN: f32 = 12.;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WeaponIconLayout>()
+            .add_systems(OnEnter(AppState::InGame), setup_hud)
+            .add_systems(
+                Update,
+                (
+                    update_experience.run_if(resource_changed::<Experience>),
+                    update_tallies.run_if(resource_changed::<Scoreboard>),
+                    update_run_clock,
+                    update_enemy_count,
+                    update_inventory,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_hud);
+    }
+}
+
+// Shared by every weapon icon, since all weapon sheets use the same grid
+#[derive(Resource)]
+struct WeaponIconLayout(Handle<TextureAtlasLayout>);
+
+impl FromWorld for WeaponIconLayout {
+    fn from_world(world: &mut World) -> Self {
+        let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+        WeaponIconLayout(layouts.add(weapon_sheet_layout()))
+    }
+}
+
+#[derive(Component)]
+struct Hud;
+
+#[derive(Component)]
+struct ExperienceFill;
+
+#[derive(Component)]
+struct LevelText;
+
+#[derive(Component)]
+struct RunClockText;
+
+#[derive(Component)]
+struct GoldText;
+
+#[derive(Component)]
+struct KillsText;
+
+#[derive(Component)]
+struct ScoreText;
+
+// Holds the count on display so the text is only rebuilt when it changes
+#[derive(Component, Default)]
+struct EnemiesText(usize);
+
+#[derive(Component)]
+struct WeaponRow;
+
+#[derive(Component)]
+struct PassiveRow;
+
+fn setup_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    experience: Res<Experience>,
+    scoreboard: Res<Scoreboard>,
+    run_time: Res<RunTime>,
+) {
+    let font = asset_server.load("fonts/quaver.ttf");
+    let text_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 12.0,
+        font,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            UI_LAYER,
+            Hud,
+        ))
+        .with_children(|parent| {
+            // Experience bar across the top with the level at its right end
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Px(EXPERIENCE_BAR_HEIGHT),
+                        justify_content: JustifyContent::FlexEnd,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.05, 0.05, 0.15).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(0.),
+                                width: Val::Percent(experience_percent(&experience)),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: Color::rgb(0.3, 0.55, 1.).into(),
+                            ..default()
+                        },
+                        ExperienceFill,
+                    ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            format!("Lv {}", experience.level),
+                            text_style.clone(),
+                        )
+                        .with_style(Style {
+                            margin: UiRect::right(Val::Px(HUD_MARGIN)),
+                            ..default()
+                        }),
+                        LevelText,
+                    ));
+                });
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        justify_content: JustifyContent::SpaceBetween,
+                        padding: UiRect::all(Val::Px(HUD_MARGIN)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                row_gap: Val::Px(ICON_GAP),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((icon_row(), WeaponRow));
+                            parent.spawn((icon_row(), PassiveRow));
+                        });
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::FlexEnd,
+                                row_gap: Val::Px(2.),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    format!("Gold: {}", scoreboard.gold),
+                                    TextStyle {
+                                        color: Color::GOLD,
+                                        ..text_style.clone()
+                                    },
+                                ),
+                                GoldText,
+                            ));
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    format!("Kills: {}", scoreboard.kills),
+                                    text_style.clone(),
+                                ),
+                                KillsText,
+                            ));
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    format!("Score: {}", scoreboard.score),
+                                    text_style.clone(),
+                                ),
+                                ScoreText,
+                            ));
+                            parent.spawn((
+                                TextBundle::from_section("Enemies: 0", text_style.clone()),
+                                EnemiesText::default(),
+                            ));
+                        });
+                });
+
+            // Centred on the screen, independent of how wide the side columns get
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.),
+                        top: Val::Px(EXPERIENCE_BAR_HEIGHT + HUD_MARGIN),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            format_run_time(run_time.elapsed_secs()),
+                            TextStyle {
+                                font_size: 20.0,
+                                ..text_style.clone()
+                            },
+                        ),
+                        RunClockText,
+                    ));
+                });
+        });
+}
+
+fn icon_row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            column_gap: Val::Px(ICON_GAP),
+            min_height: Val::Px(ICON_SIZE),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn experience_percent(experience: &Experience) -> f32 {
+    (experience.xp as f32 / experience.required() as f32).clamp(0., 1.) * 100.
+}
+
+fn update_experience(
+    experience: Res<Experience>,
+    mut fill: Query<&mut Style, With<ExperienceFill>>,
+    mut level: Query<&mut Text, With<LevelText>>,
+) {
+    for mut style in &mut fill {
+        style.width = Val::Percent(experience_percent(&experience));
+    }
+    for mut text in &mut level {
+        text.sections[0].value = format!("Lv {}", experience.level);
+    }
+}
+
+fn update_tallies(
+    scoreboard: Res<Scoreboard>,
+    mut params: ParamSet<(
+        Query<&mut Text, With<GoldText>>,
+        Query<&mut Text, With<KillsText>>,
+        Query<&mut Text, With<ScoreText>>,
+    )>,
+) {
+    for mut text in &mut params.p0() {
+        text.sections[0].value = format!("Gold: {}", scoreboard.gold);
+    }
+    for mut text in &mut params.p1() {
+        text.sections[0].value = format!("Kills: {}", scoreboard.kills);
+    }
+    for mut text in &mut params.p2() {
+        text.sections[0].value = format!("Score: {}", scoreboard.score);
+    }
+}
+
+// The clock ticks every frame, but its text only changes once a second
+fn update_run_clock(run_time: Res<RunTime>, mut clock: Query<&mut Text, With<RunClockText>>) {
+    let value = format_run_time(run_time.elapsed_secs());
+    for mut text in &mut clock {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn update_enemy_count(grid: Res<EnemyGrid>, mut counters: Query<(&mut Text, &mut EnemiesText)>) {
+    let count = grid.len();
+    for (mut text, mut shown) in &mut counters {
+        if shown.0 != count {
+            shown.0 = count;
+            text.sections[0].value = format!("Enemies: {count}");
+        }
+    }
+}
+
+// Rebuilds both icon rows whenever a weapon or passive is gained, levelled or evolved away
+#[allow(clippy::too_many_arguments)]
+fn update_inventory(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    icon_layout: Res<WeaponIconLayout>,
+    weapon_definitions: Res<WeaponDefinitions>,
+    passive_definitions: Res<PassiveDefinitions>,
+    passives: Res<Passives>,
+    weapons: Query<(Entity, Ref<Weapon>)>,
+    mut removed: RemovedComponents<Weapon>,
+    weapon_rows: Query<Entity, With<WeaponRow>>,
+    passive_rows: Query<Entity, With<PassiveRow>>,
+) {
+    let weapons_changed =
+        removed.read().count() > 0 || weapons.iter().any(|(_, weapon)| weapon.is_changed());
+    if !weapons_changed && !passives.is_changed() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/quaver.ttf");
+    let level_style = TextStyle {
+        color: Color::WHITE,
+        font_size: 8.0,
+        font,
+    };
+
+    let mut held: Vec<(Entity, &Weapon)> = weapons
+        .iter()
+        .map(|(entity, weapon)| (entity, weapon.into_inner()))
+        .collect();
+    held.sort_by_key(|(entity, _)| *entity);
+
+    for row in &weapon_rows {
+        commands.entity(row).despawn_descendants();
+        commands.entity(row).with_children(|parent| {
+            for (_, weapon) in &held {
+                let Some(definition) = weapon_definitions.get(&weapon.id) else {
+                    continue;
+                };
+                parent.spawn(icon_frame()).with_children(|parent| {
+                    parent.spawn(AtlasImageBundle {
+                        style: Style {
+                            width: Val::Percent(100.),
+                            height: Val::Percent(100.),
+                            ..default()
+                        },
+                        image: UiImage::new(definition.texture(&asset_server)),
+                        // Images are tinted by their background color
+                        background_color: definition.color().into(),
+                        texture_atlas: TextureAtlas {
+                            layout: icon_layout.0.clone(),
+                            index: definition.icon_index(),
+                        },
+                        ..default()
+                    });
+                    parent.spawn(level_badge(weapon.level as u32, &level_style));
+                });
+            }
+        });
+    }
+
+    let mut owned: Vec<(&String, &u32)> = passives.iter().collect();
+    owned.sort();
+
+    for row in &passive_rows {
+        commands.entity(row).despawn_descendants();
+        commands.entity(row).with_children(|parent| {
+            for (id, level) in &owned {
+                let Some(definition) = passive_definitions.get(*id) else {
+                    continue;
+                };
+                // Passives have no art, so they show their initials
+                let initials: String = definition
+                    .name
+                    .split_whitespace()
+                    .filter_map(|word| word.chars().next())
+                    .take(2)
+                    .collect();
+
+                parent.spawn(icon_frame()).with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        initials,
+                        TextStyle {
+                            font_size: 12.0,
+                            ..level_style.clone()
+                        },
+                    ));
+                    parent.spawn(level_badge(**level, &level_style));
+                });
+            }
+        });
+    }
+}
+
+fn icon_frame() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Px(ICON_SIZE),
+            height: Val::Px(ICON_SIZE),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            border: UiRect::all(Val::Px(1.)),
+            ..default()
+        },
+        background_color: Color::rgba(0., 0., 0., 0.6).into(),
+        border_color: Color::rgba(1., 1., 1., 0.3).into(),
+        ..default()
+    }
+}
+
+fn level_badge(level: u32, style: &TextStyle) -> TextBundle {
+    TextBundle::from_section(level.to_string(), style.clone()).with_style(Style {
+        position_type: PositionType::Absolute,
+        right: Val::Px(1.),
+        bottom: Val::Px(0.),
+        ..default()
+    })
+}
+
+fn cleanup_hud(mut commands: Commands, query: Query<Entity, With<Hud>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::constants::*;
+use crate::AppState;
+
+// Gap between the arrows and the edge of the screen
+const INDICATOR_MARGIN: f32 = 20.;
+const INDICATOR_SIZE: f32 = 32.;
+
+pub struct IndicatorPlugin;
+
+impl Plugin for IndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_indicators, update_indicators)
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(OnExit(AppState::InGame), cleanup_indicators);
+    }
+}
+
+// Entities worth pointing at while they're off-screen, like bosses and chests
+#[derive(Component)]
+pub struct Tracked {
+    pub color: Color,
+}
+
+#[derive(Component)]
+struct Indicator {
+    target: Entity,
+}
+
+#[derive(Component)]
+struct IndicatorArrow;
+
+#[derive(Component)]
+struct IndicatorDistance;
+
+fn spawn_indicators(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tracked: Query<(Entity, &Tracked), Added<Tracked>>,
+) {
+    let font = asset_server.load("fonts/quaver.ttf");
+
+    for (target, tracked) in &tracked {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(INDICATOR_SIZE),
+                        height: Val::Px(INDICATOR_SIZE),
+                        // Centred on its position rather than hanging off it
+                        margin: UiRect {
+                            left: Val::Px(-INDICATOR_SIZE / 2.),
+                            top: Val::Px(-INDICATOR_SIZE / 2.),
+                            ..default()
+                        },
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                UI_LAYER,
+                Indicator { target },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    TextBundle::from_section(
+                        ">",
+                        TextStyle {
+                            color: tracked.color,
+                            font_size: 20.0,
+                            font: font.clone(),
+                        },
+                    ),
+                    IndicatorArrow,
+                ));
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            color: Color::WHITE,
+                            font_size: 8.0,
+                            font: font.clone(),
+                        },
+                    ),
+                    IndicatorDistance,
+                ));
+            });
+    }
+}
+
+fn update_indicators(
+    mut commands: Commands,
+    camera: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    targets: Query<&GlobalTransform, With<Tracked>>,
+    mut indicators: Query<(Entity, &Indicator, &mut Style, &mut Visibility, &Children)>,
+    mut arrows: Query<&mut Transform, (With<IndicatorArrow>, Without<MainCamera>)>,
+    mut distances: Query<&mut Text, With<IndicatorDistance>>,
+) {
+    let Ok((camera, projection)) = camera.get_single() else {
+        return;
+    };
+    let view = projection.area;
+    let center = camera.translation.truncate() + view.center();
+    let half_size = view.half_size();
+
+    for (entity, indicator, mut style, mut visibility, children) in &mut indicators {
+        let Ok(target) = targets.get(indicator.target) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let offset = target.translation().truncate() - center;
+        if offset.x.abs() <= half_size.x && offset.y.abs() <= half_size.y {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+
+        // Slide along the line to the target until it meets the inset screen edge
+        let inset = half_size - Vec2::splat(INDICATOR_MARGIN);
+        let scale = (inset.x / offset.x.abs()).min(inset.y / offset.y.abs());
+        let edge = offset * scale;
+
+        // UI runs top to bottom while the world runs bottom to top
+        style.left = Val::Percent((edge.x + half_size.x) / view.width() * 100.);
+        style.top = Val::Percent((half_size.y - edge.y) / view.height() * 100.);
+
+        for &child in children {
+            if let Ok(mut arrow) = arrows.get_mut(child) {
+                arrow.rotation = Quat::from_rotation_z(-offset.to_angle());
+            }
+            if let Ok(mut text) = distances.get_mut(child) {
+                let distance = format!("{}m", (offset.length() / TILE_SIZE) as u32);
+                if text.sections[0].value != distance {
+                    text.sections[0].value = distance;
+                }
+            }
+        }
+    }
+}
+
+fn cleanup_indicators(mut commands: Commands, indicators: Query<Entity, With<Indicator>>) {
+    for entity in &indicators {
+        commands.entity(entity).despawn_recursive();
+    }
+}
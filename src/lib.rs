@@ -1,17 +1,35 @@
 use bevy::prelude::*;
+use bevy::time::Stopwatch;
 use serde::{Deserialize, Serialize};
 
 pub mod animation;
 pub mod audio_system;
 pub mod background;
+pub mod behavior;
+pub mod boss;
 pub mod camera;
 pub mod collision;
 pub mod components;
 pub mod constants;
+pub mod damage;
+pub mod elite;
 pub mod enemy;
+pub mod game_time;
+pub mod hud;
+pub mod indicator;
+pub mod loot;
 pub mod menu;
+pub mod minimap;
 pub mod pawn;
+pub mod pickup;
+pub mod progression;
+pub mod run;
 pub mod settings;
+pub mod spatial;
+pub mod spawning;
+pub mod status;
+pub mod steering;
+pub mod targeting;
 pub mod ui;
 mod utils;
 pub mod weapon;
@@ -24,14 +42,20 @@ pub enum AppState {
     OptionMenu,
     InGame,
     GameOver,
+    Victory,
 }
 
 #[derive(Resource)]
 pub struct Scoreboard {
     pub score: u32,
     pub kills: u32,
+    pub gold: u32,
 }
 
+// Time spent in the current run
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct RunTime(pub Stopwatch);
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct HighScore {
     pub score: u32,
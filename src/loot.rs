@@ -0,0 +1,439 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::boss::Boss;
+use crate::components::{Enemy, Pawn};
+use crate::constants::*;
+use crate::damage::{DamageEvent, DamageSet, DamageType, DeathEvent};
+use crate::elite::Elite;
+use crate::enemy::{handle_enemy_deaths, EnemySet, EnemySprite};
+use crate::game_time::{GameTime, PauseReason};
+use crate::pawn::{Invincible, PawnHealed};
+use crate::pickup::{spawn_pickup, Attracted, Pickup, PickupCollected, PickupKind, PickupSet};
+use crate::progression::{Experience, LevelUpMenu, Upgrade, UpgradePool};
+use crate::spatial::SpatialSet;
+use crate::targeting::Targeting;
+use crate::{AppState, Scoreboard};
+
+const DROP_SCATTER: f32 = 12.;
+
+const FOOD_HEAL: f32 = 30.;
+// Enough to kill anything but a boss
+const BOMB_DAMAGE: f32 = 10_000.;
+const INVINCIBILITY_DURATION: f32 = 10.;
+const COIN_GOLD: u32 = 1;
+
+// Odds of each chest size, as (upgrades, chance)
+const ELITE_CHEST_ODDS: [(u32, f32); 3] = [(1, 0.75), (3, 0.2), (5, 0.05)];
+const BOSS_CHEST_ODDS: [(u32, f32); 2] = [(3, 0.6), (5, 0.4)];
+
+// The chest menu runs on real time because the game is paused under it
+const SPIN_INTERVAL: f32 = 0.06;
+const FIRST_REVEAL: f32 = 0.8;
+const NEXT_REVEAL: f32 = 0.5;
+const COLLECT_KEYS: [KeyCode; 2] = [KeyCode::Space, KeyCode::Enter];
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingChests>()
+            .add_systems(OnEnter(AppState::InGame), reset_chests)
+            .add_systems(
+                FixedUpdate,
+                (
+                    drop_loot
+                        .in_set(EnemySet::Death)
+                        .before(handle_enemy_deaths),
+                    use_pickups
+                        .after(PickupSet)
+                        .after(SpatialSet)
+                        .before(DamageSet),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (open_chest, spin_chest, collect_chest)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_loot);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum LootItem {
+    Food,
+    // Hits every enemy on screen
+    Bomb,
+    // Pulls every experience gem on the floor to the pawn
+    Magnet,
+    Invincibility,
+    Chest,
+    Coin,
+}
+
+impl LootItem {
+    pub fn color(self) -> Color {
+        match self {
+            LootItem::Food => Color::rgb(0.9, 0.3, 0.25),
+            LootItem::Bomb => Color::rgb(0.3, 0.3, 0.35),
+            LootItem::Magnet => Color::rgb(0.3, 0.5, 1.),
+            LootItem::Invincibility => Color::rgb(1., 0.95, 0.6),
+            LootItem::Chest => Color::GOLD,
+            LootItem::Coin => Color::rgb(1., 0.8, 0.2),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LootDrop {
+    pub item: LootItem,
+    pub chance: f32,
+}
+
+// Chests picked up but not opened yet
+#[derive(Resource, Default)]
+struct PendingChests(Vec<u32>);
+
+#[derive(Component)]
+pub struct ChestMenu {
+    upgrades: Vec<Upgrade>,
+    labels: Vec<String>,
+    // Names flashed on the reels before they stop
+    reel: Vec<String>,
+    spin: Timer,
+    reveal: Timer,
+    revealed: usize,
+}
+
+#[derive(Component)]
+struct ChestSlot(usize);
+
+#[derive(Component)]
+struct ChestPrompt;
+
+fn reset_chests(mut pending: ResMut<PendingChests>) {
+    pending.0.clear();
+}
+
+fn roll_chest(odds: &[(u32, f32)]) -> u32 {
+    let mut roll = fastrand::f32();
+    for &(rewards, chance) in odds {
+        if roll < chance {
+            return rewards;
+        }
+        roll -= chance;
+    }
+    odds[0].0
+}
+
+fn drop_loot(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    enemies: Query<(&Transform, &EnemySprite, Has<Elite>, Has<Boss>), With<Enemy>>,
+) {
+    for death in deaths.read() {
+        let Ok((transform, enemy, elite, boss)) = enemies.get(death.entity) else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+
+        if boss {
+            let chest = PickupKind::Chest(roll_chest(&BOSS_CHEST_ODDS));
+            spawn_pickup(&mut commands, chest, position);
+        } else if elite {
+            let chest = PickupKind::Chest(roll_chest(&ELITE_CHEST_ODDS));
+            spawn_pickup(&mut commands, chest, position);
+        }
+
+        for drop in &enemy.loot {
+            if fastrand::f32() >= drop.chance {
+                continue;
+            }
+
+            let scatter = Vec2::new(fastrand::f32() - 0.5, fastrand::f32() - 0.5) * DROP_SCATTER;
+            let kind = match drop.item {
+                LootItem::Chest => PickupKind::Chest(roll_chest(&ELITE_CHEST_ODDS)),
+                item => PickupKind::Item(item),
+            };
+            spawn_pickup(&mut commands, kind, position + scatter);
+        }
+    }
+}
+
+// Everything a used pickup can act on
+#[derive(SystemParam)]
+struct PickupEffects<'w> {
+    damage_events: EventWriter<'w, DamageEvent>,
+    heal_events: EventWriter<'w, PawnHealed>,
+    scoreboard: ResMut<'w, Scoreboard>,
+}
+
+fn use_pickups(
+    mut commands: Commands,
+    mut collected: EventReader<PickupCollected>,
+    mut pending: ResMut<PendingChests>,
+    mut effects: PickupEffects,
+    targeting: Targeting,
+    mut player: Query<(Entity, &mut Pawn)>,
+    pickups: Query<(Entity, &Pickup), Without<Attracted>>,
+) {
+    let Ok((player, mut pawn)) = player.get_single_mut() else {
+        return;
+    };
+
+    for event in collected.read() {
+        match event.kind {
+            PickupKind::Item(LootItem::Food) => {
+                let amount = pawn.heal(FOOD_HEAL);
+                // Food eaten at full health isn't worth a flash
+                if amount > 0. {
+                    effects.heal_events.send(PawnHealed { amount });
+                }
+            }
+            PickupKind::Item(LootItem::Bomb) => {
+                for enemy in targeting.on_screen() {
+                    effects.damage_events.send(DamageEvent {
+                        target: enemy.entity,
+                        amount: BOMB_DAMAGE,
+                        crit: false,
+                        source: Some(player),
+                        damage_type: DamageType::Fire,
+                    });
+                }
+            }
+            PickupKind::Item(LootItem::Magnet) => {
+                for (entity, pickup) in &pickups {
+                    if matches!(pickup.kind, PickupKind::Experience(_)) {
+                        commands.entity(entity).try_insert(Attracted);
+                    }
+                }
+            }
+            PickupKind::Item(LootItem::Invincibility) => {
+                commands
+                    .entity(player)
+                    .insert(Invincible::new(INVINCIBILITY_DURATION));
+            }
+            PickupKind::Item(LootItem::Coin) => effects.scoreboard.gold += COIN_GOLD,
+            PickupKind::Chest(rewards) => pending.0.push(rewards),
+            // Chest drops are always turned into PickupKind::Chest
+            PickupKind::Item(LootItem::Chest) | PickupKind::Experience(_) => {}
+        }
+    }
+}
+
+fn open_chest(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut game_time: ResMut<GameTime>,
+    mut pending: ResMut<PendingChests>,
+    experience: Res<Experience>,
+    menus: Query<(), Or<(With<LevelUpMenu>, With<ChestMenu>)>>,
+    upgrades: UpgradePool,
+) {
+    // Level-ups are chosen first so the two menus never open together
+    if pending.0.is_empty() || experience.pending > 0 || !menus.is_empty() {
+        return;
+    }
+
+    let rewards = pending.0.remove(0);
+    let chosen = upgrades.roll(rewards as usize);
+    // Everything is maxed out, so the chest is empty
+    if chosen.is_empty() {
+        return;
+    }
+
+    let labels: Vec<String> = chosen
+        .iter()
+        .map(|upgrade| upgrades.describe(upgrade))
+        .collect();
+    let reel: Vec<String> = upgrades
+        .roll(usize::MAX)
+        .iter()
+        .map(|upgrade| upgrades.describe(upgrade))
+        .collect();
+    let slot_count = chosen.len();
+
+    game_time.pause(PauseReason::Chest);
+
+    let font = asset_server.load("fonts/quaver.ttf");
+    let texture_handle: Handle<Image> = asset_server.load("buttons/9slice.png");
+    let slicer = TextureSlicer {
+        border: BorderRect::square(16.0),
+        center_scale_mode: SliceScaleMode::Stretch,
+        sides_scale_mode: SliceScaleMode::Stretch,
+        max_corner_scale: 1.,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+            UI_LAYER,
+            ChestMenu {
+                upgrades: chosen,
+                labels,
+                reel,
+                spin: Timer::from_seconds(SPIN_INTERVAL, TimerMode::Repeating),
+                reveal: Timer::from_seconds(FIRST_REVEAL, TimerMode::Once),
+                revealed: 0,
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "Treasure!",
+                    TextStyle {
+                        color: Color::GOLD,
+                        font_size: 32.0,
+                        font: font.clone(),
+                    },
+                ),
+                UI_LAYER,
+            ));
+
+            for index in 0..slot_count {
+                parent
+                    .spawn((
+                        ImageBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                justify_content: JustifyContent::Center,
+                                width: Val::Px(320.),
+                                height: Val::Px(40.),
+                                ..default()
+                            },
+                            image: texture_handle.clone().into(),
+                            ..default()
+                        },
+                        ImageScaleMode::Sliced(slicer.clone()),
+                        UI_LAYER,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                "",
+                                TextStyle {
+                                    color: Color::WHITE,
+                                    font_size: 18.0,
+                                    font: font.clone(),
+                                },
+                            ),
+                            ChestSlot(index),
+                            UI_LAYER,
+                        ));
+                    });
+            }
+
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "Press Space to collect",
+                        TextStyle {
+                            color: Color::WHITE,
+                            font_size: 14.0,
+                            font: font.clone(),
+                        },
+                    ),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ChestPrompt,
+                UI_LAYER,
+            ));
+        });
+}
+
+// Each slot cycles through names until its turn to stop comes up
+fn spin_chest(
+    time: Res<Time<Real>>,
+    mut menus: Query<&mut ChestMenu>,
+    mut slots: Query<(&ChestSlot, &mut Text)>,
+    mut prompts: Query<&mut Visibility, With<ChestPrompt>>,
+) {
+    let Ok(mut menu) = menus.get_single_mut() else {
+        return;
+    };
+
+    let spin = menu.spin.tick(time.delta()).just_finished();
+    if menu.revealed < menu.upgrades.len() && menu.reveal.tick(time.delta()).just_finished() {
+        menu.revealed += 1;
+        menu.reveal = Timer::from_seconds(NEXT_REVEAL, TimerMode::Once);
+    }
+
+    for (slot, mut text) in &mut slots {
+        let section = &mut text.sections[0];
+        if slot.0 < menu.revealed {
+            section.value.clone_from(&menu.labels[slot.0]);
+            section.style.color = Color::GOLD;
+        } else if spin && !menu.reel.is_empty() {
+            section
+                .value
+                .clone_from(&menu.reel[fastrand::usize(..menu.reel.len())]);
+        }
+    }
+
+    if menu.revealed == menu.upgrades.len() {
+        for mut visibility in &mut prompts {
+            *visibility = Visibility::Visible;
+        }
+    }
+}
+
+fn collect_chest(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut game_time: ResMut<GameTime>,
+    mut menus: Query<(Entity, &mut ChestMenu)>,
+    mut upgrades: UpgradePool,
+) {
+    let Ok((entity, mut menu)) = menus.get_single_mut() else {
+        return;
+    };
+
+    let pressed = COLLECT_KEYS
+        .iter()
+        .any(|key| keyboard_input.just_pressed(*key))
+        || mouse_input.just_pressed(MouseButton::Left);
+    if !pressed {
+        return;
+    }
+
+    // The first press skips the spin, the second takes the rewards
+    if menu.revealed < menu.upgrades.len() {
+        menu.revealed = menu.upgrades.len();
+        return;
+    }
+
+    for upgrade in menu.upgrades.drain(..) {
+        upgrades.apply(&mut commands, upgrade);
+    }
+    commands.entity(entity).despawn_recursive();
+    game_time.resume(PauseReason::Chest);
+}
+
+fn cleanup_loot(
+    mut commands: Commands,
+    mut pending: ResMut<PendingChests>,
+    menus: Query<Entity, With<ChestMenu>>,
+) {
+    for entity in &menus {
+        commands.entity(entity).despawn_recursive();
+    }
+    pending.0.clear();
+}
@@ -10,16 +10,26 @@ use bevy_rapier2d::prelude::*;
 use bevy_survivors::constants::*;
 use bevy_survivors::{
     animation::AnimationPlugin, audio_system::AudioPlugin, background::BackgroundPlugin,
-    camera::CameraPlugin, collision::CollisionPlugin, enemy::EnemyPlugin, menu::MenuPlugin,
-    pawn::PawnPlugin, settings::SettingsPlugin, ui::UIPlugin, weapon::WeaponPlugin,
+    behavior::BehaviorPlugin, boss::BossPlugin, camera::CameraPlugin, collision::CollisionPlugin,
+    damage::DamagePlugin, elite::ElitePlugin, enemy::EnemyPlugin, game_time::GameTimePlugin,
+    hud::HudPlugin, indicator::IndicatorPlugin, loot::LootPlugin, menu::MenuPlugin,
+    minimap::MinimapPlugin, pawn::PawnPlugin, pickup::PickupPlugin, progression::ProgressionPlugin,
+    run::RunPlugin, settings::SettingsPlugin, spatial::SpatialPlugin, status::StatusPlugin,
+    ui::UIPlugin, weapon::WeaponPlugin,
 };
-use bevy_survivors::{AppState, MyCollisionEvent, ScoreEvent, Scoreboard};
+use bevy_survivors::{AppState, MyCollisionEvent, RunTime, ScoreEvent, Scoreboard};
 
 fn main() {
     App::new()
         .insert_resource(AssetMetaCheck::Never)
         .insert_resource(ClearColor(Color::BLACK))
-        .insert_resource(Scoreboard { score: 0, kills: 0 })
+        .insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
+        .insert_resource(Scoreboard {
+            score: 0,
+            kills: 0,
+            gold: 0,
+        })
+        .init_resource::<RunTime>()
         .insert_resource(PkvStore::new("kennethlove", "Survivors"))
         .init_state::<AppState>()
         .add_event::<ScoreEvent>()
@@ -46,23 +56,40 @@ fn main() {
             // LogDiagnosticsPlugin::default(),
         ))
         .add_plugins((
-            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.),
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.).in_fixed_schedule(),
             RapierDebugRenderPlugin::default(),
         ))
         .add_plugins((
             AnimationPlugin,
             AudioPlugin,
             BackgroundPlugin,
+            BehaviorPlugin,
+            BossPlugin,
             CameraPlugin,
             CollisionPlugin,
+            DamagePlugin,
+            ElitePlugin,
             EnemyPlugin,
+            GameTimePlugin,
+        ))
+        .add_plugins((
+            HudPlugin,
+            IndicatorPlugin,
+            LootPlugin,
             MenuPlugin,
+            MinimapPlugin,
             PawnPlugin,
+            PickupPlugin,
+            ProgressionPlugin,
+            RunPlugin,
             SettingsPlugin,
+            SpatialPlugin,
+            StatusPlugin,
             WeaponPlugin,
             UIPlugin,
         ))
         .add_systems(OnExit(AppState::GameOver), reset)
+        .add_systems(OnExit(AppState::Victory), reset)
         .add_systems(Update, pause.run_if(in_state(AppState::InGame)))
         // .add_systems(Update, bevy::window::close_on_esc)
         .run();
@@ -77,4 +104,5 @@ fn pause(mut state: ResMut<NextState<AppState>>, keyboard_input: Res<ButtonInput
 fn reset(mut scoreboard: ResMut<Scoreboard>) {
     scoreboard.score = 0;
     scoreboard.kills = 0;
+    scoreboard.gold = 0;
 }
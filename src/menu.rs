@@ -1,7 +1,9 @@
 use crate::components::*;
 use crate::constants::*;
+use crate::run::format_run_time;
 use crate::AppState;
 use crate::HighScore;
+use crate::RunTime;
 use crate::Scoreboard;
 use bevy::prelude::*;
 use bevy_pkv::PkvStore;
@@ -15,11 +17,14 @@ impl Plugin for MenuPlugin {
             .add_systems(OnExit(AppState::MainMenu), cleanup_main_menu)
             .add_systems(OnEnter(AppState::GameOver), setup_game_over)
             .add_systems(OnExit(AppState::GameOver), cleanup_game_over)
+            .add_systems(OnEnter(AppState::Victory), setup_victory)
+            .add_systems(OnExit(AppState::Victory), cleanup_game_over)
             .add_systems(
                 Update,
                 (
                     main_menu_button_system,
-                    game_over_button_system.run_if(in_state(AppState::InGame)),
+                    game_over_button_system
+                        .run_if(in_state(AppState::GameOver).or_else(in_state(AppState::Victory))),
                 ),
             );
     }
@@ -187,6 +192,46 @@ pub fn setup_game_over(
     asset_server: Res<AssetServer>,
     mut pkv: ResMut<PkvStore>,
     scoreboard: Res<Scoreboard>,
+    run_time: Res<RunTime>,
+) {
+    spawn_end_screen(
+        &mut commands,
+        &asset_server,
+        &mut pkv,
+        &scoreboard,
+        &run_time,
+        "Game Over",
+        Color::RED,
+    );
+}
+
+// Reached when the reaper comes for a pawn that survived the whole run
+pub fn setup_victory(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pkv: ResMut<PkvStore>,
+    scoreboard: Res<Scoreboard>,
+    run_time: Res<RunTime>,
+) {
+    spawn_end_screen(
+        &mut commands,
+        &asset_server,
+        &mut pkv,
+        &scoreboard,
+        &run_time,
+        "Victory",
+        Color::GOLD,
+    );
+}
+
+fn spawn_end_screen(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    pkv: &mut PkvStore,
+    scoreboard: &Scoreboard,
+    run_time: &RunTime,
+    title: &str,
+    title_color: Color,
 ) {
     let title_font: Handle<Font> = asset_server.load("fonts/DungeonFont.ttf");
     let body_font = asset_server.load("fonts/quaver.ttf");
@@ -230,10 +275,10 @@ pub fn setup_game_over(
         .with_children(|parent| {
             parent.spawn((
                 TextBundle::from_section(
-                    "Game Over".to_string(),
+                    title.to_string(),
                     TextStyle {
                         font_size: 100.0,
-                        color: Color::RED,
+                        color: title_color,
                         font: title_font.clone(),
                     },
                 )
@@ -251,6 +296,15 @@ pub fn setup_game_over(
                 UI_LAYER,
             ));
 
+            parent.spawn((
+                TextBundle::from_section(
+                    format!("Survived: {}", format_run_time(run_time.elapsed_secs())),
+                    body_text_style.clone(),
+                )
+                .with_text_justify(JustifyText::Center),
+                UI_LAYER,
+            ));
+
             parent.spawn((
                 TextBundle::from_section(
                     format!("High Score: {}", high_score.score),
@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::boss::Arena;
+use crate::components::Pawn;
+use crate::constants::*;
+use crate::indicator::Tracked;
+use crate::pickup::Pickup;
+use crate::settings::Settings;
+use crate::spatial::EnemyGrid;
+use crate::AppState;
+
+const MINIMAP_KEY: KeyCode = KeyCode::KeyM;
+// Texture resolution, each pixel covers MINIMAP_RANGE / MINIMAP_PIXELS world units
+const MINIMAP_PIXELS: u32 = 96;
+// Width of the world shown, centred on the pawn
+const MINIMAP_RANGE: f32 = 1536.;
+// On-screen size of the map, small enough to clear the boss bar
+const MINIMAP_SIZE: f32 = 112.;
+const MINIMAP_MARGIN: f32 = 12.;
+// Seconds between redraws
+const MINIMAP_REFRESH: f32 = 0.1;
+
+const FLOOR_COLOR: [u8; 4] = [20, 24, 30, 200];
+const WALL_COLOR: [u8; 4] = [70, 70, 84, 230];
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MinimapImage>()
+            .add_systems(OnEnter(AppState::InGame), setup_minimap)
+            .add_systems(
+                Update,
+                (
+                    toggle_minimap,
+                    show_minimap.run_if(resource_changed::<Settings>),
+                    draw_minimap,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_minimap);
+    }
+}
+
+// Texture the minimap is painted into on the CPU
+#[derive(Resource)]
+struct MinimapImage(Handle<Image>);
+
+impl FromWorld for MinimapImage {
+    fn from_world(world: &mut World) -> Self {
+        let image = Image::new_fill(
+            Extent3d {
+                width: MINIMAP_PIXELS,
+                height: MINIMAP_PIXELS,
+                ..default()
+            },
+            TextureDimension::D2,
+            &FLOOR_COLOR,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        MinimapImage(world.resource_mut::<Assets<Image>>().add(image))
+    }
+}
+
+#[derive(Component)]
+struct Minimap;
+
+fn setup_minimap(mut commands: Commands, image: Res<MinimapImage>, settings: Res<Settings>) {
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(MINIMAP_MARGIN),
+                bottom: Val::Px(MINIMAP_MARGIN),
+                width: Val::Px(MINIMAP_SIZE),
+                height: Val::Px(MINIMAP_SIZE),
+                ..default()
+            },
+            image: UiImage::new(image.0.clone()),
+            visibility: minimap_visibility(&settings),
+            ..default()
+        },
+        UI_LAYER,
+        Minimap,
+    ));
+}
+
+fn minimap_visibility(settings: &Settings) -> Visibility {
+    if settings.minimap {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn toggle_minimap(keyboard_input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keyboard_input.just_pressed(MINIMAP_KEY) {
+        settings.minimap = !settings.minimap;
+    }
+}
+
+fn show_minimap(settings: Res<Settings>, mut minimap: Query<&mut Visibility, With<Minimap>>) {
+    for mut visibility in &mut minimap {
+        *visibility = minimap_visibility(&settings);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_minimap(
+    time: Res<Time<Real>>,
+    mut since_draw: Local<f32>,
+    settings: Res<Settings>,
+    image: Res<MinimapImage>,
+    mut images: ResMut<Assets<Image>>,
+    grid: Res<EnemyGrid>,
+    player: Query<&Transform, With<Pawn>>,
+    pickups: Query<(&Transform, &Pickup)>,
+    tracked: Query<(&GlobalTransform, &Tracked)>,
+    arenas: Query<&Arena>,
+) {
+    *since_draw += time.delta_seconds();
+    if !settings.minimap || *since_draw < MINIMAP_REFRESH {
+        return;
+    }
+    *since_draw = 0.;
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let Some(image) = images.get_mut(&image.0) else {
+        return;
+    };
+
+    let center = player.translation.truncate();
+    let size = MINIMAP_PIXELS as usize;
+    let texel = MINIMAP_RANGE / MINIMAP_PIXELS as f32;
+    let origin = center - Vec2::splat(MINIMAP_RANGE / 2.);
+    // Image rows run top to bottom while the world runs bottom to top
+    let to_pixel = |position: Vec2| -> Option<usize> {
+        let local = (position - origin) / texel;
+        let (x, y) = (local.x.floor() as i32, local.y.floor() as i32);
+        (x >= 0 && y >= 0 && x < size as i32 && y < size as i32)
+            .then(|| (size - 1 - y as usize) * size + x as usize)
+    };
+
+    // Floor inside the map, wall outside it and along any arena ring
+    let half_map = Vec2::new(MAP_TILES_X as f32, MAP_TILES_Y as f32) * TILE_SIZE / 2.;
+    let floor = Rect::from_center_half_size(Vec2::ZERO, half_map);
+    let mut pixels = vec![FLOOR_COLOR; size * size];
+    for (index, pixel) in pixels.iter_mut().enumerate() {
+        let (x, y) = (index % size, size - 1 - index / size);
+        let position = origin + (Vec2::new(x as f32, y as f32) + 0.5) * texel;
+        let on_wall = arenas
+            .iter()
+            .any(|arena| (position.distance(arena.center) - arena.radius).abs() <= texel);
+        if !floor.contains(position) || on_wall {
+            *pixel = WALL_COLOR;
+        }
+    }
+
+    // Enemies as a heat map, brighter where more share a pixel
+    let mut density = vec![0u32; size * size];
+    for entry in grid.entries() {
+        if let Some(index) = to_pixel(entry.position) {
+            density[index] += 1;
+        }
+    }
+    for (pixel, &count) in pixels.iter_mut().zip(&density) {
+        if count > 0 {
+            let heat = (90 + count * 55).min(255) as u8;
+            *pixel = [heat, 30, 30, 255];
+        }
+    }
+
+    for (transform, pickup) in &pickups {
+        if let Some(index) = to_pixel(transform.translation.truncate()) {
+            pixels[index] = pickup.kind.color().as_rgba_u8();
+        }
+    }
+
+    // Tracked things and the pawn are drawn two pixels wide so they stand out
+    let mut mark = |position: Vec2, color: [u8; 4]| {
+        for offset in [Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE] {
+            if let Some(index) = to_pixel(position + offset * texel) {
+                pixels[index] = color;
+            }
+        }
+    };
+    for (transform, tracked) in &tracked {
+        mark(
+            transform.translation().truncate(),
+            tracked.color.as_rgba_u8(),
+        );
+    }
+    mark(center, [255, 255, 255, 255]);
+
+    image.data = pixels.concat();
+}
+
+fn cleanup_minimap(mut commands: Commands, minimap: Query<Entity, With<Minimap>>) {
+    for entity in &minimap {
+        commands.entity(entity).despawn_recursive();
+    }
+}
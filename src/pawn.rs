@@ -1,21 +1,58 @@
-use crate::animation::{AnimationIndices, AnimationTimer};
-use crate::collision::EnemyHitPlayer;
-use crate::components::{Enemy, Pawn};
+use crate::animation::{AnimationClip, AnimationController, AnimationState, ClipName};
+use crate::behavior::EnemyProjectile;
+use crate::collision::{EnemyHitPlayer, TouchingPawn};
+use crate::components::Pawn;
 use crate::constants::*;
+use crate::damage::{DamageEvent, DamageSet, DamageType, DeathEvent};
+use crate::enemy::{ContactCooldown, EnemySet, EnemySprite};
+use crate::status::{ApplyStatus, StatusApplication, StatusEffects};
 use crate::AppState;
 use crate::{ScoreEvent, Scoreboard};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use leafwing_input_manager::prelude::*;
 
-const IDLE_ANIMATION: AnimationIndices = AnimationIndices { first: 0, last: 1 };
-const RUN_ANIMATION: AnimationIndices = AnimationIndices { first: 1, last: 7 };
+const IDLE_ANIMATION: AnimationClip = AnimationClip::new(0, 1, 0.25);
+const RUN_ANIMATION: AnimationClip = AnimationClip::new(1, 7, 0.08);
 const STARTING_POSITION: Vec3 = Vec3::ZERO;
+const INVULNERABILITY_DURATION: f32 = 0.6;
+const FLASH_INTERVAL: f32 = 0.1;
+const INVINCIBLE_COLOR: Color = Color::rgb(1., 0.85, 0.3);
+// The glow starts blinking this many seconds before invincibility runs out
+const INVINCIBLE_WARNING: f32 = 2.;
 
 #[derive(Resource)]
 pub struct Attack {
-    pub damage_amount: f32,
     pub damage_scale: f32,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    pub area_scale: f32,
+    pub cooldown_scale: f32,
+    pub duration_scale: f32,
+    pub extra_amount: u32,
+    pub magnet_scale: f32,
+}
+
+impl Default for Attack {
+    fn default() -> Self {
+        Attack {
+            damage_scale: 1.,
+            crit_chance: 0.05,
+            crit_multiplier: 2.,
+            area_scale: 1.,
+            cooldown_scale: 1.,
+            duration_scale: 1.,
+            extra_amount: 0,
+            magnet_scale: 1.,
+        }
+    }
+}
+
+// Sent for heals worth showing, passive regeneration doesn't count
+#[derive(Event)]
+pub struct PawnHealed {
+    pub amount: f32,
 }
 
 #[derive(Event)]
@@ -23,44 +60,87 @@ struct MovementEvent {
     movement: Option<Direction2d>,
 }
 
+// Post-hit grace period during which the pawn can't be hurt
 #[derive(Component)]
-enum Direction {
+pub struct Invulnerable(Timer);
+
+impl Invulnerable {
+    pub fn new(duration: f32) -> Self {
+        Invulnerable(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+// From the invincibility pickup, blocks damage from every source rather than just contact
+#[derive(Component)]
+pub struct Invincible(Timer);
+
+impl Invincible {
+    pub fn new(duration: f32) -> Self {
+        Invincible(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+#[derive(Component)]
+pub enum Direction {
     Left,
     Right,
 }
 
+impl Direction {
+    pub fn vector(&self) -> Vec2 {
+        match self {
+            Direction::Left => Vec2::NEG_X,
+            Direction::Right => Vec2::X,
+        }
+    }
+}
+
+// Last direction the pawn moved in, kept while standing still
+#[derive(Component, Default)]
+pub struct Heading(pub Vec2);
+
 pub struct PawnPlugin;
 
 impl Plugin for PawnPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Attack {
-            damage_amount: 10.,
-            damage_scale: 1.,
-        })
-        .init_state::<PawnState>()
-        .add_plugins(InputManagerPlugin::<PawnAction>::default())
-        .add_event::<MovementEvent>()
-        .add_systems(OnEnter(AppState::InGame), spawn_pawn)
-        .add_systems(OnExit(AppState::InGame), cleanup_pawn)
-        .add_systems(
-            Update,
-            (pawn_movement, update_score, update_direction).run_if(in_state(AppState::InGame)),
-        )
-        .add_systems(
-            FixedUpdate,
-            (update_pawn_direction, collide_enemies, move_pawn).run_if(in_state(AppState::InGame)),
-        );
+        app.init_resource::<Attack>()
+            .add_plugins(InputManagerPlugin::<PawnAction>::default())
+            .add_event::<MovementEvent>()
+            .add_event::<PawnHealed>()
+            .add_systems(OnEnter(AppState::InGame), spawn_pawn)
+            .add_systems(OnExit(AppState::InGame), cleanup_pawn)
+            .add_systems(
+                Update,
+                (pawn_movement, update_score, update_direction).run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_pawn_direction,
+                    // Its status applications are read in EnemySet::Damage
+                    collide_enemies.before(EnemySet::Damage),
+                    tick_invulnerability,
+                    tick_invincibility,
+                    check_death.after(DamageSet),
+                    regenerate_health,
+                    move_pawn,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
     }
 }
 
 #[derive(Bundle)]
 struct PawnBundle {
     sprite: SpriteSheetBundle,
-    animation_indices: AnimationIndices,
-    animation_timer: AnimationTimer,
+    animation: AnimationController,
+    animation_state: AnimationState,
     pawn: Pawn,
     input_manager: InputManagerBundle<PawnAction>,
     direction: Direction,
+    heading: Heading,
+    status_effects: StatusEffects,
 }
 
 impl Default for PawnBundle {
@@ -70,14 +150,20 @@ impl Default for PawnBundle {
                 transform: Transform::from_translation(STARTING_POSITION),
                 ..default()
             },
-            animation_indices: IDLE_ANIMATION,
-            animation_timer: AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+            animation: pawn_animation(),
+            animation_state: AnimationState::Idle,
             pawn: Pawn {
                 speed: PAWN_SPEED,
                 health: 1.,
+                max_health: 1.,
+                regen: 0.,
+                armor: 0.,
+                magnet: PAWN_MAGNET,
             },
             input_manager: InputManagerBundle::with_map(PawnAction::default_input_map()),
             direction: Direction::Right,
+            heading: Heading::default(),
+            status_effects: StatusEffects::default(),
         }
     }
 }
@@ -142,7 +228,6 @@ fn spawn_pawn(
     let texture = asset_server.load("pawns/purple_knight.png");
     let layout = TextureAtlasLayout::from_grid(Vec2::new(16., 22.), 8, 1, None, None);
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let animation_indices = IDLE_ANIMATION;
 
     let mut transform = Transform::from_translation(STARTING_POSITION);
     transform.translation.z = 9.;
@@ -154,14 +239,18 @@ fn spawn_pawn(
                 texture,
                 atlas: TextureAtlas {
                     layout: texture_atlas_layout,
-                    index: animation_indices.first,
+                    index: IDLE_ANIMATION.first,
                 },
                 transform,
                 ..default()
             },
             pawn: Pawn {
                 speed: PAWN_SPEED,
-                health: 100.,
+                health: PAWN_HEALTH,
+                max_health: PAWN_HEALTH,
+                regen: PAWN_REGEN,
+                armor: 0.,
+                magnet: PAWN_MAGNET,
             },
             ..default()
         },
@@ -171,54 +260,55 @@ fn spawn_pawn(
             ..default()
         },
         Collider::cuboid(8., 11.),
-        PawnState::default(),
         ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
         ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS,
         SolverGroups::new(PAWN_WEAPON_GROUP, Group::default()),
     ));
 }
 
-#[derive(Clone, Component, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
-enum PawnState {
-    #[default]
-    Idle,
-    Running,
+// The sheet only has a walk cycle, so there's no hit clip and flinches keep running
+fn pawn_animation() -> AnimationController {
+    AnimationController::new(IDLE_ANIMATION).with_clip(ClipName::Run, RUN_ANIMATION)
 }
 
 fn move_pawn(
-    mut query: Query<&mut KinematicCharacterController, With<Pawn>>,
+    mut query: Query<
+        (
+            &mut KinematicCharacterController,
+            &mut Heading,
+            &StatusEffects,
+        ),
+        With<Pawn>,
+    >,
     mut moves: EventReader<MovementEvent>,
-    mut next_state: ResMut<NextState<PawnState>>,
     time: Res<Time>,
 ) {
     if query.is_empty() {
         return;
     }
 
-    let mut pawn = query.single_mut();
+    let (mut pawn, mut heading, effects) = query.single_mut();
+    let speed = PAWN_SPEED * effects.speed_multiplier();
 
     for event in moves.read() {
         let MovementEvent { movement } = event;
-        if movement.is_some() {
-            let direction = movement.unwrap();
+        if let Some(direction) = movement {
+            heading.0 = **direction;
             pawn.translation =
-                Some(Vec2::new(direction.x, direction.y) * time.delta_seconds() * PAWN_SPEED);
-            next_state.set(PawnState::Running);
-        } else {
-            next_state.set(PawnState::Idle);
+                Some(Vec2::new(direction.x, direction.y) * time.delta_seconds() * speed);
         }
     }
 }
 
 fn pawn_movement(
-    query: Query<&ActionState<PawnAction>, With<Pawn>>,
+    mut query: Query<(&ActionState<PawnAction>, &mut AnimationState), With<Pawn>>,
     mut event_writer: EventWriter<MovementEvent>,
 ) {
     if query.is_empty() {
         return;
     }
 
-    let action_state = query.single();
+    let (action_state, mut animation_state) = query.single_mut();
     let mut direction_vector = Vec2::ZERO;
 
     for input_direction in PawnAction::DIRECTIONS {
@@ -231,6 +321,16 @@ fn pawn_movement(
 
     let net_direction = Direction2d::new(direction_vector);
 
+    // Set from input every frame, so the pawn never gets stuck running in place
+    let moving = if net_direction.is_ok() {
+        AnimationState::Moving
+    } else {
+        AnimationState::Idle
+    };
+    if *animation_state != moving {
+        *animation_state = moving;
+    }
+
     if let Ok(direction) = net_direction {
         event_writer.send(MovementEvent {
             movement: Some(direction),
@@ -244,27 +344,135 @@ fn cleanup_pawn(mut commands: Commands, mut query: Query<Entity, With<Pawn>>) {
     }
 }
 
+// Everything an enemy landing a hit sends the pawn's way
+#[derive(SystemParam)]
+struct PawnHits<'w> {
+    damage_events: EventWriter<'w, DamageEvent>,
+    status_events: EventWriter<'w, ApplyStatus>,
+}
+
 fn collide_enemies(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut hits: PawnHits,
+    mut enemies: Query<(
+        Entity,
+        &EnemySprite,
+        &mut ContactCooldown,
+        Has<TouchingPawn>,
+    )>,
     mut events: EventReader<EnemyHitPlayer>,
-    mut player_query: Query<(&mut Pawn, &mut Sprite), Without<Enemy>>,
-    mut state: ResMut<NextState<AppState>>,
+    projectiles: Query<&EnemyProjectile>,
+    player_query: Query<(Entity, Has<Invulnerable>, Has<Invincible>), With<Pawn>>,
 ) {
-    let (mut player, mut sprite) = player_query.single_mut();
-    let mut new_health = player.health.round() as isize;
+    let Ok((player, invulnerable, invincible)) = player_query.get_single() else {
+        return;
+    };
+    let protected = invulnerable || invincible;
+
+    let mut hit = false;
+    let mut damage = |amount: f32, status: Option<StatusApplication>, source: Entity| {
+        hit = true;
+        hits.damage_events.send(DamageEvent {
+            target: player,
+            amount,
+            crit: false,
+            source: Some(source),
+            damage_type: DamageType::Physical,
+        });
+        if let Some(application) = status {
+            hits.status_events.send(ApplyStatus {
+                target: player,
+                application,
+                source: Some(source),
+            });
+        }
+    };
+
+    // Touching enemies keep hurting at their own rate for as long as they overlap
+    for (entity, enemy, mut cooldown, touching) in &mut enemies {
+        cooldown.tick(time.delta());
+        if touching && !protected && cooldown.finished() {
+            damage(enemy.contact_damage, enemy.contact_status, entity);
+            cooldown.0 = Timer::from_seconds(1. / enemy.contact_rate, TimerMode::Once);
+        }
+    }
 
-    for _ in events.read() {
-        new_health -= 1;
+    for EnemyHitPlayer(source) in events.read() {
+        if let Ok(projectile) = projectiles.get(*source) {
+            if !protected {
+                damage(projectile.damage, projectile.status, *source);
+            }
+        }
     }
 
-    if new_health <= 0 {
-        state.set(AppState::GameOver);
-    } else {
-        if new_health < player.health.round() as isize {
-            sprite.color = Color::RED;
+    if hit {
+        commands
+            .entity(player)
+            .insert(Invulnerable::new(INVULNERABILITY_DURATION));
+    }
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &mut Invulnerable, &mut Sprite), With<Pawn>>,
+) {
+    for (entity, mut invulnerable, mut sprite) in &mut player_query {
+        if invulnerable.0.tick(time.delta()).finished() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<Invulnerable>();
         } else {
+            let flash = (invulnerable.0.elapsed_secs() / FLASH_INTERVAL) as u32 % 2 == 0;
+            sprite.color = if flash {
+                Color::rgba(1., 0.4, 0.4, 0.4)
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+}
+
+fn tick_invincibility(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &mut Invincible, &mut Sprite), With<Pawn>>,
+) {
+    for (entity, mut invincible, mut sprite) in &mut player_query {
+        if invincible.0.tick(time.delta()).finished() {
             sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<Invincible>();
+        } else {
+            let blink = invincible.0.remaining_secs() < INVINCIBLE_WARNING
+                && (invincible.0.elapsed_secs() / (2. * FLASH_INTERVAL)).fract() < 0.5;
+            sprite.color = if blink {
+                Color::WHITE
+            } else {
+                INVINCIBLE_COLOR
+            };
+        }
+    }
+}
+
+fn check_death(
+    mut deaths: EventReader<DeathEvent>,
+    player_query: Query<(), With<Pawn>>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    for death in deaths.read() {
+        if player_query.contains(death.entity) {
+            state.set(AppState::GameOver);
+        }
+    }
+}
+
+fn regenerate_health(time: Res<Time>, mut player_query: Query<&mut Pawn>) {
+    for mut pawn in &mut player_query {
+        // No coming back from a killing blow
+        if pawn.health > 0. {
+            let amount = pawn.regen * time.delta_seconds();
+            pawn.heal(amount);
         }
-        player.health = new_health as f32;
     }
 }
 
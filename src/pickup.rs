@@ -0,0 +1,261 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::components::{Enemy, Pawn};
+use crate::damage::{DamageSet, DeathEvent};
+use crate::enemy::{handle_enemy_deaths, EnemySet, EnemySprite};
+use crate::indicator::Tracked;
+use crate::loot::LootItem;
+use crate::pawn::Attack;
+use crate::AppState;
+
+const PICKUP_RADIUS: f32 = 16.;
+// Attracted pickups hop away from the pawn before flying in
+const PICKUP_KICK: f32 = -120.;
+const PICKUP_ACCELERATION: f32 = 1400.;
+const PICKUP_MAX_SPEED: f32 = 900.;
+
+// Score a kill needs to be worth one point of experience
+const SCORE_PER_EXPERIENCE: f32 = 100.;
+// Past this many gems on the floor, nearby ones are merged
+const MAX_GEMS: usize = 250;
+const MERGE_CELL_SIZE: f32 = 96.;
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PickupCollected>()
+            .configure_sets(
+                FixedUpdate,
+                PickupSet
+                    .before(DamageSet)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    drop_experience
+                        .in_set(EnemySet::Death)
+                        .before(handle_enemy_deaths)
+                        .run_if(in_state(AppState::InGame)),
+                    (merge_gems, attract_pickups, move_pickups, collect_pickups)
+                        .chain()
+                        .in_set(PickupSet),
+                ),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_pickups);
+    }
+}
+
+// Collection happens here, anything reacting to PickupCollected should run after it
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PickupSet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PickupKind {
+    Experience(u32),
+    Item(LootItem),
+    // Opens into this many upgrades
+    Chest(u32),
+}
+
+impl PickupKind {
+    pub fn color(self) -> Color {
+        match self {
+            PickupKind::Experience(value) if value >= 25 => Color::rgb(1., 0.3, 0.35),
+            PickupKind::Experience(value) if value >= 5 => Color::rgb(0.35, 1., 0.45),
+            PickupKind::Experience(_) => Color::rgb(0.35, 0.6, 1.),
+            PickupKind::Item(item) => item.color(),
+            PickupKind::Chest(_) => Color::GOLD,
+        }
+    }
+
+    fn size(self) -> Vec2 {
+        match self {
+            PickupKind::Experience(value) if value >= 25 => Vec2::splat(9.),
+            PickupKind::Experience(value) if value >= 5 => Vec2::splat(7.),
+            PickupKind::Experience(_) => Vec2::splat(5.),
+            PickupKind::Item(_) => Vec2::splat(8.),
+            PickupKind::Chest(_) => Vec2::new(16., 12.),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    speed: f32,
+}
+
+// Flying toward the pawn, either from walking close or from a magnet item
+#[derive(Component)]
+pub struct Attracted;
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PickupCollected {
+    pub kind: PickupKind,
+    pub position: Vec2,
+}
+
+pub fn spawn_pickup<'a>(
+    commands: &'a mut Commands,
+    kind: PickupKind,
+    position: Vec2,
+) -> EntityCommands<'a> {
+    // Gems are drawn as diamonds
+    let rotation = match kind {
+        PickupKind::Experience(_) => Quat::from_rotation_z(std::f32::consts::FRAC_PI_4),
+        _ => Quat::IDENTITY,
+    };
+
+    let mut pickup = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(kind.size()),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(1.)).with_rotation(rotation),
+            ..default()
+        },
+        Pickup {
+            kind,
+            speed: PICKUP_KICK,
+        },
+    ));
+    // Chests are worth walking back for, so point them out once they leave the screen
+    if let PickupKind::Chest(_) = kind {
+        pickup.insert(Tracked { color: Color::GOLD });
+    }
+    pickup
+}
+
+fn drop_experience(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    enemies: Query<(&Transform, &EnemySprite), With<Enemy>>,
+) {
+    for death in deaths.read() {
+        if let Ok((transform, enemy)) = enemies.get(death.entity) {
+            let value = (enemy.score / SCORE_PER_EXPERIENCE).max(1.) as u32;
+            spawn_pickup(
+                &mut commands,
+                PickupKind::Experience(value),
+                transform.translation.truncate(),
+            );
+        }
+    }
+}
+
+fn attract_pickups(
+    mut commands: Commands,
+    attack: Res<Attack>,
+    player: Query<(&Transform, &Pawn)>,
+    pickups: Query<(Entity, &Transform), (With<Pickup>, Without<Attracted>)>,
+) {
+    let Ok((player, pawn)) = player.get_single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+    let radius = pawn.magnet * attack.magnet_scale;
+
+    for (entity, transform) in &pickups {
+        if transform.translation.truncate().distance(player_pos) <= radius {
+            commands.entity(entity).insert(Attracted);
+        }
+    }
+}
+
+fn move_pickups(
+    time: Res<Time>,
+    player: Query<&Transform, With<Pawn>>,
+    mut pickups: Query<(&mut Transform, &mut Pickup), (With<Attracted>, Without<Pawn>)>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let target = player.translation.truncate();
+
+    for (mut transform, mut pickup) in &mut pickups {
+        pickup.speed =
+            (pickup.speed + PICKUP_ACCELERATION * time.delta_seconds()).min(PICKUP_MAX_SPEED);
+
+        let offset = target - transform.translation.truncate();
+        let step = pickup.speed * time.delta_seconds();
+        // Never overshoot, or fast pickups orbit the pawn instead of landing
+        let movement = if step > 0. {
+            offset.clamp_length_max(step)
+        } else {
+            offset.normalize_or_zero() * step
+        };
+        transform.translation += movement.extend(0.);
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    mut collected: EventWriter<PickupCollected>,
+    player: Query<&Transform, With<Pawn>>,
+    pickups: Query<(Entity, &Transform, &Pickup), Without<Pawn>>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+
+    for (entity, transform, pickup) in &pickups {
+        let position = transform.translation.truncate();
+        if position.distance(player_pos) <= PICKUP_RADIUS {
+            commands.entity(entity).despawn();
+            collected.send(PickupCollected {
+                kind: pickup.kind,
+                position,
+            });
+        }
+    }
+}
+
+// Folds resting gems that share a cell into one worth their total
+fn merge_gems(
+    mut commands: Commands,
+    gems: Query<(Entity, &Transform, &Pickup), Without<Attracted>>,
+) {
+    let count = gems
+        .iter()
+        .filter(|(.., pickup)| matches!(pickup.kind, PickupKind::Experience(_)))
+        .count();
+    if count <= MAX_GEMS {
+        return;
+    }
+
+    let mut cells: HashMap<IVec2, Vec<(Entity, Vec2, u32)>> = HashMap::default();
+    for (entity, transform, pickup) in &gems {
+        if let PickupKind::Experience(value) = pickup.kind {
+            let position = transform.translation.truncate();
+            let cell = (position / MERGE_CELL_SIZE).floor().as_ivec2();
+            cells
+                .entry(cell)
+                .or_default()
+                .push((entity, position, value));
+        }
+    }
+
+    for gems in cells.values().filter(|gems| gems.len() > 1) {
+        let total: u32 = gems.iter().map(|(.., value)| value).sum();
+        let center =
+            gems.iter().map(|(_, position, _)| *position).sum::<Vec2>() / gems.len() as f32;
+
+        for (entity, ..) in gems {
+            commands.entity(*entity).despawn();
+        }
+        spawn_pickup(&mut commands, PickupKind::Experience(total), center);
+    }
+}
+
+fn cleanup_pickups(mut commands: Commands, pickups: Query<Entity, With<Pickup>>) {
+    for entity in &pickups {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,404 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::constants::*;
+use crate::game_time::{GameTime, PauseReason};
+use crate::loot::ChestMenu;
+use crate::pickup::{PickupCollected, PickupKind, PickupSet};
+use crate::settings::Settings;
+use crate::weapon::{spawn_weapon, PassiveDefinitions, Passives, Weapon, WeaponDefinitions};
+use crate::AppState;
+
+const MAX_WEAPONS: usize = 6;
+const MAX_PASSIVES: usize = 6;
+const CHOICE_COUNT: usize = 3;
+const CHOICE_KEYS: [KeyCode; CHOICE_COUNT] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3];
+// The world slows to this speed for a moment before the level-up menu opens
+const LEVEL_UP_SLOW_MOTION: f32 = 0.2;
+// Real seconds
+const LEVEL_UP_SLOW_MOTION_DURATION: f32 = 0.6;
+
+pub struct ProgressionPlugin;
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Experience>()
+            .add_systems(OnEnter(AppState::InGame), reset_experience)
+            .add_systems(
+                FixedUpdate,
+                gain_experience
+                    .after(PickupSet)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (open_level_up, choose_upgrade)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), close_level_up);
+    }
+}
+
+#[derive(Resource)]
+pub struct Experience {
+    pub level: u32,
+    pub xp: u32,
+    // Level-ups earned but not yet chosen
+    pub pending: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Experience {
+            level: 1,
+            xp: 0,
+            pending: 0,
+        }
+    }
+}
+
+impl Experience {
+    pub fn required(&self) -> u32 {
+        5 + (self.level - 1) * 10
+    }
+
+    pub fn add(&mut self, amount: u32) {
+        self.xp += amount;
+        while self.xp >= self.required() {
+            self.xp -= self.required();
+            self.level += 1;
+            self.pending += 1;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Upgrade {
+    NewWeapon(String),
+    WeaponLevel(Entity),
+    Evolve(Entity, String),
+    NewPassive(String),
+    PassiveLevel(String),
+}
+
+#[derive(Component)]
+pub struct LevelUpMenu;
+
+// `index` is the button's place in the menu, picking its CHOICE_KEYS shortcut
+#[derive(Component)]
+struct UpgradeButton {
+    index: usize,
+    upgrade: Upgrade,
+}
+
+fn reset_experience(mut experience: ResMut<Experience>) {
+    *experience = Experience::default();
+}
+
+fn gain_experience(
+    mut collected: EventReader<PickupCollected>,
+    mut experience: ResMut<Experience>,
+    mut game_time: ResMut<GameTime>,
+) {
+    let was_pending = experience.pending > 0;
+    for event in collected.read() {
+        if let PickupKind::Experience(amount) = event.kind {
+            experience.add(amount);
+        }
+    }
+
+    if !was_pending && experience.pending > 0 {
+        game_time.slow_motion(LEVEL_UP_SLOW_MOTION, LEVEL_UP_SLOW_MOTION_DURATION);
+    }
+}
+
+// Everything needed to offer, describe and hand out upgrades
+#[derive(SystemParam)]
+pub struct UpgradePool<'w, 's> {
+    asset_server: Res<'w, AssetServer>,
+    texture_atlas_layouts: ResMut<'w, Assets<TextureAtlasLayout>>,
+    settings: Res<'w, Settings>,
+    passives: ResMut<'w, Passives>,
+    weapon_definitions: Res<'w, WeaponDefinitions>,
+    passive_definitions: Res<'w, PassiveDefinitions>,
+    weapons: Query<'w, 's, (Entity, &'static mut Weapon)>,
+}
+
+impl<'w, 's> UpgradePool<'w, 's> {
+    // Up to `count` distinct upgrades that can all be taken together
+    pub fn roll(&self, count: usize) -> Vec<Upgrade> {
+        let mut evolutions = Vec::new();
+        let mut new_weapons = Vec::new();
+        let mut new_passives = Vec::new();
+        let mut pool = Vec::new();
+
+        for (entity, weapon) in self.weapons.iter() {
+            let Some(definition) = self.weapon_definitions.get(&weapon.id) else {
+                continue;
+            };
+            if weapon.level < definition.max_level() {
+                pool.push(Upgrade::WeaponLevel(entity));
+            } else if let Some(evolution) = &definition.evolution {
+                if self.passives.contains_key(&evolution.passive) {
+                    evolutions.push(Upgrade::Evolve(entity, evolution.into.clone()));
+                }
+            }
+        }
+
+        for (id, definition) in self.weapon_definitions.iter() {
+            let owned = self.weapons.iter().any(|(_, weapon)| &weapon.id == id);
+            if !owned && !definition.evolved {
+                new_weapons.push(Upgrade::NewWeapon(id.clone()));
+            }
+        }
+
+        for (id, definition) in self.passive_definitions.iter() {
+            match self.passives.get(id) {
+                Some(&level) if level < definition.max_level => {
+                    pool.push(Upgrade::PassiveLevel(id.clone()))
+                }
+                None => new_passives.push(Upgrade::NewPassive(id.clone())),
+                _ => {}
+            }
+        }
+
+        // Only offer as many new items as there are free slots
+        fastrand::shuffle(&mut new_weapons);
+        fastrand::shuffle(&mut new_passives);
+        let weapon_slots = MAX_WEAPONS.saturating_sub(self.weapons.iter().count());
+        let passive_slots = MAX_PASSIVES.saturating_sub(self.passives.len());
+        pool.extend(new_weapons.into_iter().take(weapon_slots));
+        pool.extend(new_passives.into_iter().take(passive_slots));
+
+        // Evolutions are always offered, the rest of the slots are random
+        fastrand::shuffle(&mut pool);
+        evolutions.extend(pool);
+        evolutions.truncate(count);
+        evolutions
+    }
+
+    pub fn describe(&self, upgrade: &Upgrade) -> String {
+        let weapon_name = |entity: &Entity| {
+            self.weapons
+                .get(*entity)
+                .ok()
+                .and_then(|(_, weapon)| self.weapon_definitions.get(&weapon.id))
+                .map_or("", |definition| definition.name.as_str())
+        };
+        let passive_name = |id: &String| {
+            self.passive_definitions
+                .get(id)
+                .map_or("", |definition| definition.name.as_str())
+        };
+
+        match upgrade {
+            Upgrade::NewWeapon(id) => format!(
+                "New: {}",
+                self.weapon_definitions
+                    .get(id)
+                    .map_or("", |definition| definition.name.as_str())
+            ),
+            Upgrade::WeaponLevel(entity) => {
+                let level = self
+                    .weapons
+                    .get(*entity)
+                    .map_or(0, |(_, weapon)| weapon.level);
+                format!("{} Lv {}", weapon_name(entity), level + 1)
+            }
+            Upgrade::Evolve(entity, into) => format!(
+                "Evolve {} into {}",
+                weapon_name(entity),
+                self.weapon_definitions
+                    .get(into)
+                    .map_or("", |definition| definition.name.as_str())
+            ),
+            Upgrade::NewPassive(id) => format!("New: {}", passive_name(id)),
+            Upgrade::PassiveLevel(id) => format!(
+                "{} Lv {}",
+                passive_name(id),
+                self.passives.get(id).copied().unwrap_or(0) + 1
+            ),
+        }
+    }
+
+    pub fn apply(&mut self, commands: &mut Commands, upgrade: Upgrade) {
+        match upgrade {
+            Upgrade::NewWeapon(id) => {
+                if let Some(definition) = self.weapon_definitions.get(&id) {
+                    spawn_weapon(
+                        commands,
+                        &self.asset_server,
+                        &mut self.texture_atlas_layouts,
+                        &self.settings,
+                        &id,
+                        definition,
+                    );
+                }
+            }
+            Upgrade::WeaponLevel(entity) => {
+                if let Ok((_, mut weapon)) = self.weapons.get_mut(entity) {
+                    if let Some(definition) = self.weapon_definitions.get(&weapon.id) {
+                        let level = weapon.level + 1;
+                        weapon.set_level(definition, level);
+                    }
+                }
+            }
+            Upgrade::Evolve(entity, into) => {
+                if let Some(definition) = self.weapon_definitions.get(&into) {
+                    commands.entity(entity).despawn();
+                    spawn_weapon(
+                        commands,
+                        &self.asset_server,
+                        &mut self.texture_atlas_layouts,
+                        &self.settings,
+                        &into,
+                        definition,
+                    );
+                }
+            }
+            Upgrade::NewPassive(id) | Upgrade::PassiveLevel(id) => {
+                *self.passives.entry(id).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+fn open_level_up(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut game_time: ResMut<GameTime>,
+    mut experience: ResMut<Experience>,
+    menus: Query<(), Or<(With<LevelUpMenu>, With<ChestMenu>)>>,
+    upgrades: UpgradePool,
+) {
+    // Let the level-up slow-motion play out before the menu freezes everything
+    if experience.pending == 0 || !menus.is_empty() || game_time.is_slowed() {
+        return;
+    }
+
+    let choices = upgrades.roll(CHOICE_COUNT);
+    // Everything is maxed out, so there is nothing to choose
+    if choices.is_empty() {
+        experience.pending = 0;
+        return;
+    }
+
+    game_time.pause(PauseReason::LevelUp);
+
+    let font = asset_server.load("fonts/quaver.ttf");
+    let texture_handle: Handle<Image> = asset_server.load("buttons/9slice.png");
+    let slicer = TextureSlicer {
+        border: BorderRect::square(16.0),
+        center_scale_mode: SliceScaleMode::Stretch,
+        sides_scale_mode: SliceScaleMode::Stretch,
+        max_corner_scale: 1.,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(12.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+            UI_LAYER,
+            LevelUpMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    format!("Level {}", experience.level + 1 - experience.pending),
+                    TextStyle {
+                        color: Color::GOLD,
+                        font_size: 32.0,
+                        font: font.clone(),
+                    },
+                ),
+                UI_LAYER,
+            ));
+
+            for (index, upgrade) in choices.into_iter().enumerate() {
+                let label = upgrades.describe(&upgrade);
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                justify_content: JustifyContent::Center,
+                                width: Val::Px(320.),
+                                height: Val::Px(50.),
+                                ..default()
+                            },
+                            image: texture_handle.clone().into(),
+                            ..default()
+                        },
+                        ImageScaleMode::Sliced(slicer.clone()),
+                        UpgradeButton { index, upgrade },
+                        UI_LAYER,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                format!("{}. {}", index + 1, label),
+                                TextStyle {
+                                    color: Color::WHITE,
+                                    font_size: 18.0,
+                                    font: font.clone(),
+                                },
+                            ),
+                            UI_LAYER,
+                        ));
+                    });
+            }
+        });
+}
+
+fn choose_upgrade(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut game_time: ResMut<GameTime>,
+    mut experience: ResMut<Experience>,
+    buttons: Query<(&Interaction, &UpgradeButton)>,
+    menus: Query<Entity, With<LevelUpMenu>>,
+    mut upgrades: UpgradePool,
+) {
+    let Ok(menu) = menus.get_single() else {
+        return;
+    };
+
+    let pressed = buttons
+        .iter()
+        .find(|(interaction, button)| {
+            **interaction == Interaction::Pressed
+                || CHOICE_KEYS
+                    .get(button.index)
+                    .is_some_and(|key| keyboard_input.just_pressed(*key))
+        })
+        .map(|(_, button)| button.upgrade.clone());
+    let Some(upgrade) = pressed else {
+        return;
+    };
+
+    upgrades.apply(&mut commands, upgrade);
+
+    commands.entity(menu).despawn_recursive();
+    experience.pending -= 1;
+    if experience.pending == 0 {
+        game_time.resume(PauseReason::LevelUp);
+    }
+}
+
+fn close_level_up(mut commands: Commands, menus: Query<Entity, With<LevelUpMenu>>) {
+    for entity in &menus {
+        commands.entity(entity).despawn_recursive();
+    }
+}
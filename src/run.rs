@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+use crate::{AppState, RunTime};
+
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ReaperEvent>()
+            .add_systems(OnEnter(AppState::InGame), reset_run_time)
+            .add_systems(
+                FixedUpdate,
+                (tick_run_time, summon_reaper, end_run)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+// Sent once when the run has lasted its full length
+#[derive(Event)]
+pub struct ReaperEvent;
+
+// Minutes and seconds, as shown on the clock
+pub fn format_run_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.) as u32;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn reset_run_time(mut run_time: ResMut<RunTime>) {
+    run_time.reset();
+}
+
+fn tick_run_time(time: Res<Time>, mut run_time: ResMut<RunTime>) {
+    run_time.tick(time.delta());
+}
+
+fn summon_reaper(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    run_time: Res<RunTime>,
+    mut reaper_events: EventWriter<ReaperEvent>,
+) {
+    let elapsed = run_time.elapsed_secs();
+    let previous = elapsed - time.delta_seconds();
+    if previous < settings.run_length && elapsed >= settings.run_length {
+        reaper_events.send(ReaperEvent);
+    }
+}
+
+fn end_run(mut reaper_events: EventReader<ReaperEvent>, mut state: ResMut<NextState<AppState>>) {
+    if reaper_events.read().next().is_some() {
+        state.set(AppState::Victory);
+    }
+}
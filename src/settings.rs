@@ -1,14 +1,28 @@
 use bevy::prelude::*;
 use bevy_pkv::PkvStore;
+use bevy_rapier2d::prelude::*;
+
+use crate::constants::{DEFAULT_RUN_LENGTH, FIXED_TIMESTEP_HZ};
 
 #[derive(Resource)]
 pub struct Settings {
     pub volume: f32,
+    pub tick_rate: f64,
+    pub damage_numbers: bool,
+    // Seconds
+    pub run_length: f32,
+    pub minimap: bool,
 }
 
 impl Default for Settings {
     fn default() -> Self {
-        Settings { volume: 1. }
+        Settings {
+            volume: 1.,
+            tick_rate: FIXED_TIMESTEP_HZ,
+            damage_numbers: true,
+            run_length: DEFAULT_RUN_LENGTH,
+            minimap: true,
+        }
     }
 }
 
@@ -18,7 +32,13 @@ impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Settings::default())
             .add_systems(Startup, load_settings)
-            .add_systems(Update, save_settings);
+            .add_systems(
+                Update,
+                (
+                    save_settings.run_if(resource_changed::<Settings>),
+                    apply_tick_rate,
+                ),
+            );
     }
 }
 
@@ -28,8 +48,47 @@ fn load_settings(mut settings: ResMut<Settings>, pkv: Res<PkvStore>) {
         Err(_) => 1.,
     };
     settings.volume = volume;
+
+    let tick_rate: f64 = match pkv.get::<f64>("tick_rate") {
+        Ok(tick_rate) if tick_rate > 0. => tick_rate,
+        _ => FIXED_TIMESTEP_HZ,
+    };
+    settings.tick_rate = tick_rate;
+
+    settings.damage_numbers = pkv.get::<bool>("damage_numbers").unwrap_or(true);
+
+    settings.run_length = match pkv.get::<f32>("run_length") {
+        Ok(run_length) if run_length > 0. => run_length,
+        _ => DEFAULT_RUN_LENGTH,
+    };
+
+    settings.minimap = pkv.get::<bool>("minimap").unwrap_or(true);
 }
 
 fn save_settings(settings: Res<Settings>, mut pkv: ResMut<PkvStore>) {
     pkv.set::<f32>("volume", &settings.volume).unwrap();
+    pkv.set::<f64>("tick_rate", &settings.tick_rate).unwrap();
+    pkv.set::<bool>("damage_numbers", &settings.damage_numbers)
+        .unwrap();
+    pkv.set::<f32>("run_length", &settings.run_length).unwrap();
+    pkv.set::<bool>("minimap", &settings.minimap).unwrap();
+}
+
+// Keep FixedUpdate and the physics step running at the same rate
+fn apply_tick_rate(
+    settings: Res<Settings>,
+    mut applied: Local<f64>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    if *applied == settings.tick_rate {
+        return;
+    }
+    *applied = settings.tick_rate;
+
+    fixed_time.set_timestep_hz(settings.tick_rate);
+    rapier_config.timestep_mode = TimestepMode::Fixed {
+        dt: (1. / settings.tick_rate) as f32,
+        substeps: 1,
+    };
 }
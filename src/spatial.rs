@@ -0,0 +1,292 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier2d::prelude::*;
+
+use crate::components::Enemy;
+use crate::enemy::{EnemySet, EnemySprite};
+use crate::AppState;
+
+const ENEMY_CELL_SIZE: f32 = 64.;
+
+pub struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnemyGrid>()
+            .configure_sets(
+                FixedUpdate,
+                SpatialSet
+                    .before(EnemySet::Steering)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(FixedUpdate, rebuild_enemy_grid.in_set(SpatialSet))
+            .add_systems(OnExit(AppState::InGame), clear_enemy_grid);
+    }
+}
+
+// Anything reading the grid should run after this to see the current tick
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SpatialSet;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GridEntry {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub health: f32,
+}
+
+// Uniform grid of square cells, each listing the entries inside it
+pub struct SpatialGrid {
+    cell_size: f32,
+    entries: Vec<GridEntry>,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialGrid {
+            cell_size,
+            entries: Vec::new(),
+            cells: HashMap::default(),
+        }
+    }
+
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn rebuild(&mut self, entries: impl IntoIterator<Item = GridEntry>) {
+        self.entries.clear();
+        self.entries.extend(entries);
+
+        // Keep the cell vectors around so their allocations get reused
+        for indices in self.cells.values_mut() {
+            indices.clear();
+        }
+        for index in 0..self.entries.len() {
+            let cell = self.cell(self.entries[index].position);
+            self.cells.entry(cell).or_default().push(index);
+        }
+        self.cells.retain(|_, indices| !indices.is_empty());
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.cells.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[GridEntry] {
+        &self.entries
+    }
+
+    pub fn within(&self, center: Vec2, radius: f32) -> impl Iterator<Item = &GridEntry> {
+        let min = self.cell(center - Vec2::splat(radius));
+        let max = self.cell(center + Vec2::splat(radius));
+
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .map(|&index| &self.entries[index])
+            .filter(move |entry| entry.position.distance_squared(center) <= radius * radius)
+    }
+
+    pub fn any_within(&self, center: Vec2, radius: f32) -> bool {
+        self.within(center, radius).next().is_some()
+    }
+
+    // Grows the search one ring of cells at a time until nothing closer can turn up
+    pub fn nearest(&self, from: Vec2, count: usize, range: f32) -> Vec<GridEntry> {
+        let mut found: Vec<(f32, GridEntry)> = Vec::new();
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let center = self.cell(from);
+        let max_ring = (range / self.cell_size).ceil() as i32 + 1;
+        for ring in 0..=max_ring {
+            for x in -ring..=ring {
+                for y in -ring..=ring {
+                    if x.abs() != ring && y.abs() != ring {
+                        continue;
+                    }
+                    let Some(indices) = self.cells.get(&(center + IVec2::new(x, y))) else {
+                        continue;
+                    };
+                    for &index in indices {
+                        let entry = self.entries[index];
+                        let distance = entry.position.distance(from);
+                        if distance <= range {
+                            found.push((distance, entry));
+                        }
+                    }
+                }
+            }
+
+            found.sort_by(|a, b| a.0.total_cmp(&b.0));
+            found.truncate(count);
+            // Anything in the next ring is at least this far away
+            let ring_distance = ring as f32 * self.cell_size;
+            if found.len() == count && found[count - 1].0 <= ring_distance {
+                break;
+            }
+        }
+
+        found.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    // Centre of the cell group near `from` with the most entries within `radius` of it
+    pub fn densest(&self, from: Vec2, range: f32, radius: f32) -> Option<Vec2> {
+        self.cells
+            .values()
+            .filter_map(|indices| {
+                let centroid = indices
+                    .iter()
+                    .map(|&index| self.entries[index].position)
+                    .sum::<Vec2>()
+                    / indices.len() as f32;
+                (centroid.distance(from) <= range).then_some(centroid)
+            })
+            .map(|centroid| (self.within(centroid, radius).count(), centroid))
+            .max_by_key(|(count, _)| *count)
+            .map(|(_, centroid)| centroid)
+    }
+}
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct EnemyGrid(pub SpatialGrid);
+
+impl Default for EnemyGrid {
+    fn default() -> Self {
+        EnemyGrid(SpatialGrid::new(ENEMY_CELL_SIZE))
+    }
+}
+
+fn rebuild_enemy_grid(
+    mut grid: ResMut<EnemyGrid>,
+    enemies: Query<(Entity, &Transform, &Velocity, &EnemySprite), With<Enemy>>,
+) {
+    grid.rebuild(enemies.iter().filter(|(.., enemy)| enemy.health > 0.).map(
+        |(entity, transform, velocity, enemy)| GridEntry {
+            entity,
+            position: transform.translation.truncate(),
+            velocity: velocity.linvel,
+            health: enemy.health,
+        },
+    ));
+}
+
+fn clear_enemy_grid(mut grid: ResMut<EnemyGrid>) {
+    grid.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(positions: &[Vec2]) -> SpatialGrid {
+        let mut grid = SpatialGrid::new(ENEMY_CELL_SIZE);
+        grid.rebuild(
+            positions
+                .iter()
+                .enumerate()
+                .map(|(index, &position)| GridEntry {
+                    entity: Entity::from_raw(index as u32),
+                    position,
+                    velocity: Vec2::ZERO,
+                    health: 1.,
+                }),
+        );
+        grid
+    }
+
+    fn indices<'a>(entries: impl IntoIterator<Item = &'a GridEntry>) -> Vec<u32> {
+        let mut indices: Vec<u32> = entries
+            .into_iter()
+            .map(|entry| entry.entity.index())
+            .collect();
+        indices.sort();
+        indices
+    }
+
+    #[test]
+    fn within_matches_a_scan_across_cells() {
+        let positions: Vec<Vec2> = (-10..10)
+            .flat_map(|x| (-10..10).map(move |y| Vec2::new(x as f32 * 23., y as f32 * 17.)))
+            .collect();
+        let grid = grid(&positions);
+
+        for (center, radius) in [
+            (Vec2::ZERO, 50.),
+            (Vec2::new(-100., 40.), 80.),
+            (Vec2::new(63.9, -0.1), 10.),
+        ] {
+            let expected: Vec<u32> = positions
+                .iter()
+                .enumerate()
+                .filter(|(_, position)| position.distance(center) <= radius)
+                .map(|(index, _)| index as u32)
+                .collect();
+            assert_eq!(indices(grid.within(center, radius)), expected);
+        }
+    }
+
+    #[test]
+    fn within_includes_the_edge_of_the_radius() {
+        let grid = grid(&[Vec2::new(100., 0.), Vec2::new(100.5, 0.)]);
+        assert_eq!(indices(grid.within(Vec2::ZERO, 100.)), vec![0]);
+    }
+
+    #[test]
+    fn nearest_is_sorted_and_limited_to_range() {
+        let grid = grid(&[
+            Vec2::new(300., 0.),
+            Vec2::new(0., 40.),
+            Vec2::new(-20., 0.),
+            Vec2::new(0., -150.),
+        ]);
+
+        let found = grid.nearest(Vec2::ZERO, 3, 200.);
+        let order: Vec<u32> = found.iter().map(|entry| entry.entity.index()).collect();
+        assert_eq!(order, vec![2, 1, 3]);
+
+        assert!(grid.nearest(Vec2::ZERO, 0, 200.).is_empty());
+        assert!(grid.nearest(Vec2::new(1_000., 1_000.), 1, 200.).is_empty());
+    }
+
+    #[test]
+    fn nearest_looks_past_its_own_cell() {
+        // The entry sharing a cell with `from` is further away than one next door
+        let grid = grid(&[Vec2::new(60., 60.), Vec2::new(70., 1.)]);
+        let found = grid.nearest(Vec2::new(1., 1.), 1, 200.);
+        assert_eq!(found[0].entity.index(), 1);
+    }
+
+    #[test]
+    fn densest_picks_the_crowd_in_range() {
+        let mut positions = vec![Vec2::new(10., 10.)];
+        positions.extend((0..5).map(|index| Vec2::new(200. + index as f32 * 4., 200.)));
+        let grid = grid(&positions);
+
+        let spot = grid.densest(Vec2::ZERO, 1_000., 32.).unwrap();
+        assert!(spot.distance(Vec2::new(208., 200.)) < 1.);
+
+        // The crowd is out of range, leaving only the lone entry
+        let spot = grid.densest(Vec2::ZERO, 100., 32.).unwrap();
+        assert!(spot.distance(Vec2::new(10., 10.)) < 1.);
+
+        assert_eq!(
+            SpatialGrid::new(ENEMY_CELL_SIZE).densest(Vec2::ZERO, 100., 32.),
+            None
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::components::Pawn;
+use crate::constants::*;
+use crate::pawn::Heading;
+use crate::spatial::EnemyGrid;
+
+// Gap between the corner of the screen and the inside of the spawn ring
+const SPAWN_MARGIN: f32 = 32.;
+const SPAWN_RING_WIDTH: f32 = 96.;
+// How much room a new enemy needs from the ones already on the field
+const SPAWN_CLEARANCE: f32 = 24.;
+// Half-angle of the cone used for directional spawns
+const SPAWN_SPREAD: f32 = std::f32::consts::FRAC_PI_4;
+// Candidates tried before giving up on a spawn for this tick
+const SPAWN_ATTEMPTS: usize = 12;
+// Keep spawns this far inside the edge of the floor
+const MAP_PADDING: f32 = 64.;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SpawnSide {
+    #[default]
+    Around,
+    // Where the pawn is heading, so running away doesn't leave the swarm behind
+    Ahead,
+    Behind,
+}
+
+// Picks spawn positions on a ring just outside the camera view
+#[derive(SystemParam)]
+pub struct SpawnPlacement<'w, 's> {
+    grid: Res<'w, EnemyGrid>,
+    camera: Query<'w, 's, (&'static Transform, &'static OrthographicProjection), With<MainCamera>>,
+    player: Query<'w, 's, (&'static Transform, &'static Heading), With<Pawn>>,
+}
+
+impl<'w, 's> SpawnPlacement<'w, 's> {
+    pub fn find(&self, side: SpawnSide) -> Option<Vec3> {
+        let (center, inner) = self.ring()?;
+        let outer = inner + SPAWN_RING_WIDTH;
+
+        let heading = self
+            .player
+            .get_single()
+            .map_or(Vec2::ZERO, |(_, heading)| heading.0);
+        let direction = match side {
+            SpawnSide::Ahead => heading,
+            SpawnSide::Behind => -heading,
+            SpawnSide::Around => Vec2::ZERO,
+        };
+
+        for _ in 0..SPAWN_ATTEMPTS {
+            let angle = if direction == Vec2::ZERO {
+                fastrand::f32() * std::f32::consts::TAU
+            } else {
+                direction.to_angle() + (fastrand::f32() * 2. - 1.) * SPAWN_SPREAD
+            };
+            // Spread evenly over the ring's area rather than bunching on the inside
+            let distance =
+                (inner * inner + fastrand::f32() * (outer * outer - inner * inner)).sqrt();
+            let position = center + Vec2::from_angle(angle) * distance;
+
+            if !self.is_blocked(position) {
+                return Some(position.extend(2.));
+            }
+        }
+
+        None
+    }
+
+    // Center of the view and the distance from it to just past a corner
+    fn ring(&self) -> Option<(Vec2, f32)> {
+        if let Ok((transform, projection)) = self.camera.get_single() {
+            let center = transform.translation.truncate() + projection.area.center();
+            let half_diagonal = projection.area.half_size().length();
+            return Some((center, half_diagonal + SPAWN_MARGIN));
+        }
+
+        let (transform, _) = self.player.get_single().ok()?;
+        let half_diagonal = Vec2::new(WIDTH, HEIGHT).length() / 2.;
+        Some((
+            transform.translation.truncate(),
+            half_diagonal + SPAWN_MARGIN,
+        ))
+    }
+
+    fn is_blocked(&self, position: Vec2) -> bool {
+        let half_map = Vec2::new(MAP_TILES_X as f32, MAP_TILES_Y as f32) * TILE_SIZE / 2.;
+        let bounds = Rect::from_center_half_size(Vec2::ZERO, half_map - MAP_PADDING);
+
+        !bounds.contains(position) || self.grid.any_within(position, SPAWN_CLEARANCE)
+    }
+}
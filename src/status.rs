@@ -0,0 +1,403 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+use crate::behavior::BehaviorState;
+use crate::components::{Enemy, Pawn};
+use crate::damage::{DamageEvent, DamageSet, DamageType};
+use crate::enemy::{EnemySet, EnemySprite, HitStun};
+use crate::pawn::{Invincible, Invulnerable};
+use crate::AppState;
+
+// Damage-over-time effects deal their damage on this interval
+const TICK_INTERVAL: f32 = 0.5;
+const PARTICLE_LIFETIME: f32 = 0.6;
+const PARTICLE_RISE: f32 = 24.;
+// How far an affected sprite is pulled toward its status colour
+const TINT_STRENGTH: f32 = 0.6;
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatus>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_enemy_status_speed
+                        .after(EnemySet::Steering)
+                        .before(EnemySet::Damage),
+                    (apply_statuses, tick_enemy_statuses)
+                        .chain()
+                        .in_set(EnemySet::Damage),
+                    tick_pawn_statuses.before(DamageSet),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (animate_particles, tint_statuses).run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), cleanup_particles);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum StatusKind {
+    Burn,
+    Freeze,
+    Poison,
+    Slow,
+    Bleed,
+}
+
+impl StatusKind {
+    // How many applications can pile up; 1 means a new application only refreshes
+    fn max_stacks(self) -> u32 {
+        match self {
+            StatusKind::Poison => 5,
+            StatusKind::Bleed => 10,
+            _ => 1,
+        }
+    }
+
+    fn deals_damage(self) -> bool {
+        matches!(
+            self,
+            StatusKind::Burn | StatusKind::Poison | StatusKind::Bleed
+        )
+    }
+
+    fn color(self) -> Color {
+        match self {
+            StatusKind::Burn => Color::ORANGE,
+            StatusKind::Freeze => Color::rgb(0.6, 0.9, 1.),
+            StatusKind::Poison => Color::LIME_GREEN,
+            StatusKind::Slow => Color::rgb(0.6, 0.5, 1.),
+            StatusKind::Bleed => Color::CRIMSON,
+        }
+    }
+
+    fn damage_type(self) -> DamageType {
+        match self {
+            StatusKind::Burn => DamageType::Fire,
+            StatusKind::Freeze => DamageType::Ice,
+            StatusKind::Poison => DamageType::Poison,
+            StatusKind::Bleed => DamageType::Bleed,
+            StatusKind::Slow => DamageType::Physical,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct StatusApplication {
+    pub kind: StatusKind,
+    pub duration: f32,
+    // Damage per tick for damage-over-time effects, fraction of speed removed for slows
+    pub magnitude: f32,
+    #[serde(default = "always")]
+    pub chance: f32,
+}
+
+fn always() -> f32 {
+    1.
+}
+
+#[derive(Event)]
+pub struct ApplyStatus {
+    pub target: Entity,
+    pub application: StatusApplication,
+    pub source: Option<Entity>,
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub magnitude: f32,
+    pub stacks: u32,
+    pub duration: Timer,
+    // Whoever applied the latest stack gets credit for the kill
+    pub source: Option<Entity>,
+}
+
+#[derive(Component, Debug)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+    tick: Timer,
+}
+
+impl Default for StatusEffects {
+    fn default() -> Self {
+        StatusEffects {
+            effects: Vec::new(),
+            tick: Timer::from_seconds(TICK_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl StatusEffects {
+    pub fn add(&mut self, application: &StatusApplication, source: Option<Entity>) {
+        let duration = Timer::from_seconds(application.duration, TimerMode::Once);
+
+        match self
+            .effects
+            .iter_mut()
+            .find(|effect| effect.kind == application.kind)
+        {
+            Some(effect) => {
+                effect.stacks = (effect.stacks + 1).min(application.kind.max_stacks());
+                effect.magnitude = effect.magnitude.max(application.magnitude);
+                effect.duration = duration;
+                effect.source = source;
+            }
+            None => self.effects.push(StatusEffect {
+                kind: application.kind,
+                magnitude: application.magnitude,
+                stacks: 1,
+                duration,
+                source,
+            }),
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|effect| match effect.kind {
+                StatusKind::Freeze => 0.,
+                StatusKind::Slow => (1. - effect.magnitude).clamp(0., 1.),
+                _ => 1.,
+            })
+            .product()
+    }
+
+    // Advances every effect and returns the damage dealt by each effect if a tick happened
+    fn tick(
+        &mut self,
+        delta: std::time::Duration,
+    ) -> Option<Vec<(StatusKind, f32, Option<Entity>)>> {
+        for effect in &mut self.effects {
+            effect.duration.tick(delta);
+        }
+
+        let ticked = self.tick.tick(delta).just_finished();
+        let damage = ticked.then(|| {
+            self.effects
+                .iter()
+                .filter(|effect| effect.kind.deals_damage())
+                .map(|effect| {
+                    let amount = effect.magnitude * effect.stacks as f32;
+                    (effect.kind, amount, effect.source)
+                })
+                .collect()
+        });
+
+        self.effects.retain(|effect| !effect.duration.finished());
+        if self.effects.is_empty() {
+            self.tick.reset();
+        }
+
+        damage
+    }
+}
+
+// Remembers the colour a sprite had before a status tinted it
+#[derive(Component)]
+struct StatusTint {
+    base: Color,
+    applied: Color,
+}
+
+#[derive(Component)]
+struct StatusParticle {
+    lifetime: Timer,
+}
+
+fn apply_statuses(
+    mut events: EventReader<ApplyStatus>,
+    mut targets: Query<(&mut StatusEffects, Option<&EnemySprite>)>,
+) {
+    for ApplyStatus {
+        target,
+        application,
+        source,
+    } in events.read()
+    {
+        let Ok((mut effects, enemy)) = targets.get_mut(*target) else {
+            continue;
+        };
+        if enemy.is_some_and(|enemy| enemy.immunities.contains(&application.kind)) {
+            continue;
+        }
+        if fastrand::f32() >= application.chance {
+            continue;
+        }
+
+        effects.add(application, *source);
+    }
+}
+
+fn apply_enemy_status_speed(
+    mut enemies: Query<(&StatusEffects, &mut Velocity), (With<Enemy>, Without<HitStun>)>,
+) {
+    for (effects, mut velocity) in &mut enemies {
+        if !effects.effects.is_empty() {
+            velocity.linvel *= effects.speed_multiplier();
+        }
+    }
+}
+
+fn tick_enemy_statuses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut enemies: Query<(Entity, &Transform, &mut StatusEffects), With<Enemy>>,
+) {
+    for (entity, transform, mut effects) in &mut enemies {
+        if effects.effects.is_empty() {
+            continue;
+        }
+
+        if let Some(damage) = effects.tick(time.delta()) {
+            for (kind, amount, source) in damage {
+                damage_events.send(DamageEvent {
+                    target: entity,
+                    amount,
+                    crit: false,
+                    source,
+                    damage_type: kind.damage_type(),
+                });
+            }
+            spawn_particles(&mut commands, transform, &effects);
+        }
+    }
+}
+
+fn tick_pawn_statuses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut player: Query<(Entity, &Transform, &mut StatusEffects), (With<Pawn>, Without<Enemy>)>,
+) {
+    for (entity, transform, mut effects) in &mut player {
+        if effects.effects.is_empty() {
+            continue;
+        }
+
+        if let Some(damage) = effects.tick(time.delta()) {
+            for (kind, amount, source) in damage {
+                damage_events.send(DamageEvent {
+                    target: entity,
+                    amount,
+                    crit: false,
+                    source,
+                    damage_type: kind.damage_type(),
+                });
+            }
+            spawn_particles(&mut commands, transform, &effects);
+        }
+    }
+}
+
+fn spawn_particles(commands: &mut Commands, transform: &Transform, effects: &StatusEffects) {
+    for effect in &effects.effects {
+        let offset = Vec2::new(fastrand::f32() - 0.5, fastrand::f32() - 0.5) * 12.;
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: effect.kind.color(),
+                    custom_size: Some(Vec2::splat(3.)),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    (transform.translation.truncate() + offset).extend(5.),
+                ),
+                ..default()
+            },
+            StatusParticle {
+                lifetime: Timer::from_seconds(PARTICLE_LIFETIME, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn animate_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Transform, &mut Sprite, &mut StatusParticle)>,
+) {
+    for (entity, mut transform, mut sprite, mut particle) in &mut particles {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += PARTICLE_RISE * time.delta_seconds();
+        sprite.color.set_a(1. - particle.lifetime.fraction());
+    }
+}
+
+// Shades the sprite by its newest status, then restores it once every status wears off.
+// A charger's telegraph and the pawn's hit flash and invincibility glow own the colour
+// while they last.
+fn tint_statuses(
+    mut commands: Commands,
+    mut targets: Query<
+        (
+            Entity,
+            &StatusEffects,
+            &mut Sprite,
+            Option<&mut StatusTint>,
+            Option<&BehaviorState>,
+        ),
+        (Without<Invulnerable>, Without<Invincible>),
+    >,
+) {
+    for (entity, effects, mut sprite, tint, behavior) in &mut targets {
+        if behavior.is_some_and(|behavior| behavior.is_telegraphing()) {
+            continue;
+        }
+
+        let Some(effect) = effects.effects.last() else {
+            if let Some(tint) = tint {
+                if sprite.color == tint.applied {
+                    sprite.color = tint.base;
+                }
+                commands.entity(entity).remove::<StatusTint>();
+            }
+            continue;
+        };
+
+        let [r, g, b, _] = effect.kind.color().as_rgba_f32();
+        let shade = Vec3::ONE.lerp(Vec3::new(r, g, b), TINT_STRENGTH);
+
+        match tint {
+            Some(mut tint) => {
+                // Something else recoloured the sprite, so tint over its new colour
+                if sprite.color != tint.applied {
+                    tint.base = sprite.color;
+                }
+                tint.applied = tint.base * shade;
+                sprite.color = tint.applied;
+            }
+            None => {
+                let base = sprite.color;
+                sprite.color = base * shade;
+                commands.entity(entity).insert(StatusTint {
+                    base,
+                    applied: sprite.color,
+                });
+            }
+        }
+    }
+}
+
+fn cleanup_particles(mut commands: Commands, query: Query<Entity, With<StatusParticle>>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
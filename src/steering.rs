@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct SteeringWeights {
+    pub seek: f32,
+    pub separation: f32,
+    pub alignment: f32,
+    pub orbit: f32,
+    pub separation_radius: f32,
+    pub alignment_radius: f32,
+    pub orbit_radius: f32,
+}
+
+impl Default for SteeringWeights {
+    fn default() -> Self {
+        SteeringWeights {
+            seek: 1.,
+            separation: 1.5,
+            alignment: 0.2,
+            orbit: 0.,
+            separation_radius: 24.,
+            alignment_radius: 48.,
+            orbit_radius: 0.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Neighbor {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+// Blends seek, separation, alignment and orbit into a heading no longer than 1
+pub fn steering_direction(
+    weights: &SteeringWeights,
+    entity: Entity,
+    position: Vec2,
+    target: Vec2,
+    neighbors: impl IntoIterator<Item = Neighbor>,
+) -> Vec2 {
+    let to_target = target - position;
+    let distance = to_target.length();
+    let seek = to_target.normalize_or_zero();
+
+    let mut separation = Vec2::ZERO;
+    let mut heading_sum = Vec2::ZERO;
+    let mut heading_count = 0;
+
+    for neighbor in neighbors {
+        if neighbor.entity == entity {
+            continue;
+        }
+
+        let offset = position - neighbor.position;
+        let neighbor_distance = offset.length();
+
+        if neighbor_distance < weights.separation_radius {
+            // Stacked bodies have no offset to push along, so pick a direction
+            let away = if neighbor_distance > f32::EPSILON {
+                offset / neighbor_distance
+            } else {
+                Vec2::from_angle(fastrand::f32() * std::f32::consts::TAU)
+            };
+            separation += away * (1. - neighbor_distance / weights.separation_radius);
+        }
+
+        if neighbor_distance < weights.alignment_radius {
+            heading_sum += neighbor.velocity.normalize_or_zero();
+            heading_count += 1;
+        }
+    }
+
+    let alignment = if heading_count > 0 {
+        (heading_sum / heading_count as f32).normalize_or_zero()
+    } else {
+        Vec2::ZERO
+    };
+
+    // Circle the target instead of running straight into it once close enough
+    let mut orbit = Vec2::ZERO;
+    let mut seek_weight = weights.seek;
+    if weights.orbit > 0. && distance < weights.orbit_radius {
+        orbit = seek.perp();
+        seek_weight *= distance / weights.orbit_radius;
+    }
+
+    (seek * seek_weight
+        + separation * weights.separation
+        + alignment * weights.alignment
+        + orbit * weights.orbit)
+        .clamp_length_max(1.)
+}
@@ -0,0 +1,101 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::camera::MainCamera;
+use crate::spatial::{EnemyGrid, GridEntry};
+
+// Radius used to score how crowded a spot is
+const CLUSTER_RADIUS: f32 = 64.;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+pub enum TargetMode {
+    Nearest,
+    // Middle of the biggest group of enemies
+    Densest,
+    LowestHealth,
+    #[default]
+    Random,
+}
+
+// Enemy lookups for weapons that aim, answered from the enemy grid
+#[derive(SystemParam)]
+pub struct Targeting<'w, 's> {
+    grid: Res<'w, EnemyGrid>,
+    camera: Query<'w, 's, (&'static Transform, &'static OrthographicProjection), With<MainCamera>>,
+}
+
+impl<'w, 's> Targeting<'w, 's> {
+    pub fn is_empty(&self) -> bool {
+        self.grid.is_empty()
+    }
+
+    pub fn nearest(&self, from: Vec2, count: usize, range: f32) -> Vec<GridEntry> {
+        self.grid.nearest(from, count, range)
+    }
+
+    pub fn densest(&self, from: Vec2, range: f32) -> Option<Vec2> {
+        self.grid.densest(from, range, CLUSTER_RADIUS)
+    }
+
+    pub fn lowest_health(&self, center: Vec2, radius: f32) -> Option<GridEntry> {
+        self.grid
+            .within(center, radius)
+            .min_by(|a, b| a.health.total_cmp(&b.health))
+            .copied()
+    }
+
+    fn visible(&self) -> Rect {
+        self.camera
+            .get_single()
+            .map_or(Rect::default(), |(transform, projection)| {
+                let center = transform.translation.truncate();
+                Rect::from_corners(center + projection.area.min, center + projection.area.max)
+            })
+    }
+
+    pub fn on_screen(&self) -> Vec<GridEntry> {
+        let visible = self.visible();
+        self.grid
+            .within(visible.center(), visible.half_size().length())
+            .filter(|entry| visible.contains(entry.position))
+            .copied()
+            .collect()
+    }
+
+    // Distinct on-screen enemies within `radius` of `center`, in random order
+    pub fn random_visible(&self, center: Vec2, radius: f32, count: usize) -> Vec<GridEntry> {
+        let visible = self.visible();
+        let mut targets: Vec<GridEntry> = self
+            .grid
+            .within(center, radius)
+            .filter(|entry| visible.contains(entry.position))
+            .copied()
+            .collect();
+        fastrand::shuffle(&mut targets);
+        targets.truncate(count);
+        targets
+    }
+
+    // Up to `count` positions to aim at for the given mode
+    pub fn aim(&self, mode: TargetMode, from: Vec2, range: f32, count: usize) -> Vec<Vec2> {
+        match mode {
+            TargetMode::Nearest => self
+                .nearest(from, count, range)
+                .iter()
+                .map(|entry| entry.position)
+                .collect(),
+            TargetMode::Densest => self
+                .densest(from, range)
+                .map_or_else(Vec::new, |position| vec![position; count]),
+            TargetMode::LowestHealth => self
+                .lowest_health(from, range)
+                .map_or_else(Vec::new, |entry| vec![entry.position; count]),
+            TargetMode::Random => self
+                .random_visible(from, range, count)
+                .iter()
+                .map(|entry| entry.position)
+                .collect(),
+        }
+    }
+}
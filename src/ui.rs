@@ -1,7 +1,22 @@
+use crate::boss::Boss;
 use crate::components::*;
 use crate::constants::*;
-use crate::{AppState, Scoreboard};
+use crate::damage::DamageDealt;
+use crate::enemy::EnemySprite;
+use crate::pawn::PawnHealed;
+use crate::AppState;
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+const HEALTH_BAR_SIZE: Vec2 = Vec2::new(32., 4.);
+// Distance below the pawn's center, clear of its feet
+const HEALTH_BAR_OFFSET: f32 = 28.;
+const HEALTH_BAR_Z: f32 = 10.;
+const HEALTH_FLASH_DURATION: f32 = 0.15;
+// Fraction of max health below which the screen edges start to pulse
+const LOW_HEALTH: f32 = 0.3;
+const VIGNETTE_WIDTH: f32 = 48.;
+const VIGNETTE_PULSE_RATE: f32 = 4.;
 
 pub struct UIPlugin;
 
@@ -10,11 +25,11 @@ impl Plugin for UIPlugin {
         app.add_systems(OnEnter(AppState::MainMenu), setup_title)
             .add_systems(OnExit(AppState::MainMenu), cleanup_title)
             .add_systems(OnExit(AppState::InGame), cleanup_ui)
-            .add_systems(OnEnter(AppState::InGame), (setup_ui, setup_hp))
-            .add_systems(OnExit(AppState::InGame), cleanup_hp)
+            .add_systems(OnEnter(AppState::InGame), (setup_hp, setup_boss_bar))
+            .add_systems(OnExit(AppState::InGame), (cleanup_hp, cleanup_boss_bar))
             .add_systems(
                 Update,
-                (update_ui, update_hp).run_if(in_state(AppState::InGame)),
+                (update_hp, update_boss_bar).run_if(in_state(AppState::InGame)),
             );
     }
 }
@@ -50,149 +65,230 @@ fn menu_button_system(
     }
 }
 
-fn setup_hp(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut player: Query<(&Transform, &Pawn)>,
-) {
-    let mut location: &Transform = &Transform::from_translation(Vec3::new(0., 0., 0.));
-    let mut pawn: &Pawn = &Pawn {
-        health: 1.,
-        speed: 1.,
-    };
-
-    if !player.is_empty() {
-        let pl = player.single_mut();
-        location = pl.0;
-        pawn = pl.1;
-    }
-
-    let mut location = *location;
-    location.translation.y += 20.;
-
-    let font = asset_server.load("fonts/quaver.ttf");
-
-    let text_style = TextStyle {
-        color: Color::WHITE,
-        font_size: 16.0,
-        font,
-    };
+fn setup_hp(mut commands: Commands) {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.1, 0.02, 0.02, 0.8),
+                    custom_size: Some(HEALTH_BAR_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., 0., HEALTH_BAR_Z),
+                ..default()
+            },
+            PlayerHealth,
+        ))
+        .with_children(|parent| {
+            // Anchored on its left edge so shrinking the width drains it toward the left
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::CRIMSON,
+                        custom_size: Some(HEALTH_BAR_SIZE),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(-HEALTH_BAR_SIZE.x / 2., 0., 0.1),
+                    ..default()
+                },
+                PlayerHealthFill::default(),
+            ));
+        });
 
     commands.spawn((
-        Text2dBundle {
-            text: Text::from_section(pawn.health.to_string(), text_style.clone()),
-            transform: location.clone(),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                border: UiRect::all(Val::Px(VIGNETTE_WIDTH)),
+                ..default()
+            },
+            border_color: Color::NONE.into(),
             ..default()
         },
-        PlayerHealth,
+        UI_LAYER,
+        LowHealthVignette,
     ));
 }
 
 fn update_hp(
-    mut player: Query<(&Transform, &Pawn), Without<PlayerHealth>>,
-    mut hp: Query<(&mut Text, &mut Transform), With<PlayerHealth>>,
+    time: Res<Time<Real>>,
+    mut damage_events: EventReader<DamageDealt>,
+    mut heal_events: EventReader<PawnHealed>,
+    player: Query<(Entity, &Transform, &Pawn), Without<PlayerHealth>>,
+    mut bar: Query<&mut Transform, With<PlayerHealth>>,
+    mut fill: Query<(&mut Sprite, &mut PlayerHealthFill)>,
+    mut vignette: Query<&mut BorderColor, With<LowHealthVignette>>,
 ) {
-    if player.is_empty() {
+    let Ok((player, location, pawn)) = player.get_single() else {
         return;
+    };
+    let ratio = (pawn.health / pawn.max_health).clamp(0., 1.);
+
+    for mut transform in &mut bar {
+        transform.translation.x = location.translation.x;
+        transform.translation.y = location.translation.y - HEALTH_BAR_OFFSET;
     }
 
-    let (location, pawn) = player.single_mut();
-    let mut location = *location;
-    location.translation.y += 20.;
+    // Drain both readers in full so a heal frame doesn't leave hits queued for the next
+    let healed = heal_events.read().count() > 0;
+    let hit = damage_events
+        .read()
+        .filter(|event| event.target == player)
+        .count()
+        > 0;
 
-    for (mut text, mut transform) in &mut hp {
-        transform.translation = location.translation;
-        text.sections[0].value = pawn.health.to_string();
+    // Heals win over hits landing on the same frame
+    let flash = if healed {
+        Some(Color::LIME_GREEN)
+    } else if hit {
+        Some(Color::WHITE)
+    } else {
+        None
+    };
+
+    for (mut sprite, mut health_fill) in &mut fill {
+        sprite.custom_size = Some(Vec2::new(HEALTH_BAR_SIZE.x * ratio, HEALTH_BAR_SIZE.y));
+
+        if let Some(color) = flash {
+            health_fill.flash = Timer::from_seconds(HEALTH_FLASH_DURATION, TimerMode::Once);
+            health_fill.flash_color = color;
+        }
+        health_fill.flash.tick(time.delta());
+        sprite.color = if health_fill.flash.finished() {
+            Color::CRIMSON
+        } else {
+            health_fill.flash_color
+        };
+    }
+
+    // Fades in below the threshold, pulsing harder and faster the closer to death
+    let danger = 1. - ratio / LOW_HEALTH;
+    for mut border in &mut vignette {
+        border.0 = if danger > 0. && pawn.health > 0. {
+            let rate = VIGNETTE_PULSE_RATE * (1. + danger);
+            let pulse = 0.5 + 0.5 * (time.elapsed_seconds() * rate).sin();
+            Color::rgba(0.7, 0., 0., danger * (0.25 + 0.35 * pulse))
+        } else {
+            Color::NONE
+        };
     }
 }
 
-fn cleanup_hp(mut commands: Commands, query: Query<Entity, With<PlayerHealth>>) {
+fn cleanup_hp(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<PlayerHealth>, With<LowHealthVignette>)>>,
+) {
     for entity in &query {
         commands.entity(entity).despawn_recursive();
     }
 }
 
-fn setup_ui(
+fn cleanup_ui(
     mut commands: Commands,
-    enemies: Query<Entity, With<Enemy>>,
-    asset_server: Res<AssetServer>,
-    score: Res<Scoreboard>,
+    interaction_query: Query<(Entity, &Interaction, &mut UiImage), With<Button>>,
 ) {
-    let font = asset_server.load("fonts/quaver.ttf");
+    for entity in &mut interaction_query.iter() {
+        commands.entity(entity.0).despawn_recursive();
+    }
+}
 
-    let text_style = TextStyle {
-        color: Color::WHITE,
-        font_size: 12.0,
-        font,
-    };
+fn setup_boss_bar(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/quaver.ttf");
 
     commands
         .spawn((
             NodeBundle {
                 style: Style {
-                    width: Val::Percent(100.),
-                    height: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(60.),
+                    left: Val::Percent(20.),
+                    bottom: Val::Px(20.),
                     flex_direction: FlexDirection::Column,
-                    justify_content: JustifyContent::FlexStart,
-                    align_items: AlignItems::FlexStart,
-                    margin: UiRect {
-                        left: Val::Px(20.),
-                        right: Val::Px(0.),
-                        top: Val::Px(20.),
-                        bottom: Val::Px(0.),
-                    },
+                    align_items: AlignItems::Center,
                     ..default()
                 },
+                visibility: Visibility::Hidden,
                 ..default()
             },
             UI_LAYER,
+            BossBar,
         ))
         .with_children(|parent| {
-            parent.spawn((
-                TextBundle::from_section(format!("Score {}", score.score), text_style.clone()),
-                Score,
-            ));
-            parent.spawn((
-                TextBundle::from_section(format!("Kills {}", score.kills), text_style.clone()),
-                Kills,
-            ));
             parent.spawn((
                 TextBundle::from_section(
-                    format!("Enemies {}", enemies.iter().count()),
-                    text_style.clone(),
+                    "",
+                    TextStyle {
+                        color: Color::WHITE,
+                        font_size: 16.0,
+                        font,
+                    },
                 ),
-                Enemies,
+                BossName,
             ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Px(12.),
+                        margin: UiRect::top(Val::Px(4.)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.2, 0.05, 0.05).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: Color::CRIMSON.into(),
+                            ..default()
+                        },
+                        BossBarFill,
+                    ));
+                });
         });
 }
 
-fn update_ui(
-    mut params: ParamSet<(
-        Query<&mut Text, With<Score>>,
-        Query<&mut Text, With<Kills>>,
-        Query<&mut Text, With<Enemies>>,
-    )>,
-    enemies: Query<Entity, With<Enemy>>,
-    scoreboard: Res<Scoreboard>,
+fn update_boss_bar(
+    bosses: Query<(&Boss, &EnemySprite)>,
+    mut bar: Query<&mut Visibility, With<BossBar>>,
+    mut fill: Query<&mut Style, With<BossBarFill>>,
+    mut name: Query<&mut Text, With<BossName>>,
 ) {
-    for mut text in &mut params.p0() {
-        text.sections[0].value = format!("Score: {}", scoreboard.score);
-    }
-    for mut text in &mut params.p1() {
-        text.sections[0].value = format!("Kills: {}", scoreboard.kills);
+    let boss = bosses.iter().next();
+
+    for mut visibility in &mut bar {
+        *visibility = if boss.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
     }
 
-    for mut text in &mut params.p2() {
-        text.sections[0].value = format!("Enemies: {}", enemies.iter().count());
+    let Some((boss, enemy)) = boss else {
+        return;
+    };
+
+    for mut style in &mut fill {
+        style.width = Val::Percent((enemy.health / boss.max_health).clamp(0., 1.) * 100.);
+    }
+    for mut text in &mut name {
+        if text.sections[0].value != boss.title {
+            text.sections[0].value = boss.title.clone();
+        }
     }
 }
 
-fn cleanup_ui(
-    mut commands: Commands,
-    interaction_query: Query<(Entity, &Interaction, &mut UiImage), With<Button>>,
-) {
-    for entity in &mut interaction_query.iter() {
-        commands.entity(entity.0).despawn_recursive();
+fn cleanup_boss_bar(mut commands: Commands, query: Query<Entity, With<BossBar>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
@@ -234,13 +330,33 @@ fn cleanup_title(mut commands: Commands, query: Query<Entity, With<Text>>) {
 }
 
 #[derive(Component)]
-struct Score;
+struct PlayerHealth;
 
 #[derive(Component)]
-struct Kills;
+struct PlayerHealthFill {
+    flash: Timer,
+    flash_color: Color,
+}
+
+impl Default for PlayerHealthFill {
+    fn default() -> Self {
+        let mut flash = Timer::from_seconds(HEALTH_FLASH_DURATION, TimerMode::Once);
+        flash.tick(flash.duration());
+        PlayerHealthFill {
+            flash,
+            flash_color: Color::WHITE,
+        }
+    }
+}
 
 #[derive(Component)]
-struct Enemies;
+struct LowHealthVignette;
 
 #[derive(Component)]
-struct PlayerHealth;
+struct BossBar;
+
+#[derive(Component)]
+struct BossBarFill;
+
+#[derive(Component)]
+struct BossName;
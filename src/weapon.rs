@@ -1,10 +1,17 @@
+use std::collections::HashMap;
+
 use crate::{
     animation::{AnimationIndices, AnimationTimer},
     components::*,
     constants::*,
+    pawn::{Attack, Direction},
     settings::Settings,
+    spatial::SpatialSet,
+    status::StatusApplication,
+    targeting::{TargetMode, Targeting},
     AppState, MyCollisionEvent,
 };
+use bevy::asset::ron;
 use bevy::{
     audio::{AudioBundle, PlaybackMode, Volume},
     math::bounding::IntersectsVolume,
@@ -15,28 +22,318 @@ use bevy::{
 };
 // use bevy_kira_audio::prelude::*;
 use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
 
 const STARTING_POSITION: Vec3 = Vec3::ZERO;
+const STARTING_WEAPON: &str = "fire_ring";
+pub const MAX_WEAPON_LEVEL: usize = 8;
+const MIN_COOLDOWN: f32 = 0.05;
 
 pub struct WeaponPlugin;
 
 impl Plugin for WeaponPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::InGame), setup_sprite)
+        app.init_resource::<Passives>()
+            .add_systems(Startup, load_weapon_definitions)
+            .add_systems(OnEnter(AppState::InGame), setup_sprite)
             .add_systems(OnExit(AppState::InGame), cleanup_sprite)
-            .add_systems(FixedUpdate, move_weapon.run_if(in_state(AppState::InGame)))
-            .add_systems(Update, update_volume);
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_passives.run_if(resource_changed::<Passives>),
+                    (move_weapon, fire_weapons.after(SpatialSet)),
+                    (orbit_satellites, follow_slashes, expire_hitboxes),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(Update, update_volume.run_if(resource_changed::<Settings>));
     }
 }
 
-#[derive(Clone, Component)]
-pub struct Weapon {
-    audio_filename: String,
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WeaponStats {
+    pub damage: f32,
+    // Multiplier on the weapon's size
+    pub area: f32,
+    // Seconds between hits on the same enemy
+    pub cooldown: f32,
+    pub amount: u32,
+    // Seconds the weapon's effects last
+    pub duration: f32,
+}
+
+impl WeaponStats {
+    fn plus(self, other: &WeaponStats) -> WeaponStats {
+        WeaponStats {
+            damage: self.damage + other.damage,
+            area: self.area + other.area,
+            cooldown: self.cooldown + other.cooldown,
+            amount: self.amount + other.amount,
+            duration: self.duration + other.duration,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+pub enum WeaponKind {
+    // Always-on ring around the pawn
+    #[default]
+    Aura,
+    // Satellites circling the pawn, speed in radians per second
+    Orbit {
+        radius: f32,
+        speed: f32,
+    },
+    // Strikes in the facing direction, alternating sides with each extra amount
+    Slash {
+        reach: f32,
+    },
+    // Drops on enemies within range of the pawn, picked by the weapon's targeting
+    GroundTarget {
+        range: f32,
+    },
+}
+
+// Swaps a max-level weapon for another one once the passive is owned
+#[derive(Clone, Debug, Deserialize)]
+pub struct Evolution {
+    pub passive: String,
+    pub into: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WeaponDefinition {
+    pub name: String,
     filename: String,
-    pub damage_frame_end: usize,
+    audio_filename: String,
     pub damage_frame_start: usize,
+    pub damage_frame_end: usize,
+    #[serde(default)]
+    pub kind: WeaponKind,
+    // Where aimed weapons drop their hitboxes
+    #[serde(default)]
+    targeting: TargetMode,
+    #[serde(default = "default_frames")]
+    frames: AnimationIndices,
+    #[serde(default = "default_frame_time")]
+    frame_time: f32,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    // In sheet pixels, before scaling
+    #[serde(default = "default_collider_radius")]
+    collider_radius: f32,
+    // Seconds between hits on the same enemy for weapons that fire on a cooldown
+    #[serde(default = "default_hit_interval")]
+    hit_interval: f32,
+    #[serde(default)]
+    tint: Option<(f32, f32, f32)>,
+    base: WeaponStats,
+    // What each level past the first adds to the base stats
+    #[serde(default)]
+    levels: Vec<WeaponStats>,
+    knockback: f32,
+    stun: f32,
+    #[serde(default)]
+    on_hit: Option<StatusApplication>,
+    #[serde(default)]
+    pub evolution: Option<Evolution>,
+    // Evolved weapons are only reachable through their evolution
+    #[serde(default)]
+    pub evolved: bool,
+}
+
+fn default_frames() -> AnimationIndices {
+    AnimationIndices { first: 0, last: 23 }
+}
+
+fn default_frame_time() -> f32 {
+    0.05
 }
 
+fn default_scale() -> f32 {
+    2.
+}
+
+fn default_collider_radius() -> f32 {
+    32.
+}
+
+fn default_hit_interval() -> f32 {
+    0.5
+}
+
+impl WeaponDefinition {
+    pub fn max_level(&self) -> usize {
+        (self.levels.len() + 1).min(MAX_WEAPON_LEVEL)
+    }
+
+    pub fn texture(&self, asset_server: &AssetServer) -> Handle<Image> {
+        asset_server.load(&self.filename)
+    }
+
+    // Middle of the damage window, where the effect is at its fullest
+    pub fn icon_index(&self) -> usize {
+        (self.damage_frame_start + self.damage_frame_end) / 2
+    }
+
+    pub fn color(&self) -> Color {
+        self.tint
+            .map_or(Color::WHITE, |(r, g, b)| Color::rgb(r, g, b))
+    }
+
+    fn stats_at(&self, level: usize) -> WeaponStats {
+        self.levels
+            .iter()
+            .take(level.saturating_sub(1))
+            .fold(self.base, |stats, delta| stats.plus(delta))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PassiveBonus {
+    pub damage: f32,
+    pub area: f32,
+    // Fraction taken off every weapon's cooldown
+    pub cooldown: f32,
+    pub duration: f32,
+    pub amount: u32,
+    pub crit_chance: f32,
+    pub magnet: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PassiveDefinition {
+    pub name: String,
+    pub max_level: u32,
+    per_level: PassiveBonus,
+}
+
+#[derive(Deserialize)]
+struct WeaponData {
+    weapons: HashMap<String, WeaponDefinition>,
+    passives: HashMap<String, PassiveDefinition>,
+}
+
+#[derive(Resource, Deref)]
+pub struct WeaponDefinitions(HashMap<String, WeaponDefinition>);
+
+#[derive(Resource, Deref)]
+pub struct PassiveDefinitions(HashMap<String, PassiveDefinition>);
+
+// Passive items the pawn owns, by id, with their level
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct Passives(pub HashMap<String, u32>);
+
+#[derive(Clone, Component)]
+pub struct Weapon {
+    pub id: String,
+    pub kind: WeaponKind,
+    pub targeting: TargetMode,
+    pub level: usize,
+    pub stats: WeaponStats,
+    hit_interval: f32,
+    pub knockback: f32,
+    // Seconds an enemy stops steering after being hit
+    pub stun: f32,
+    pub on_hit: Option<StatusApplication>,
+}
+
+impl Weapon {
+    fn new(id: &str, definition: &WeaponDefinition, level: usize) -> Self {
+        Weapon {
+            id: id.to_string(),
+            kind: definition.kind,
+            targeting: definition.targeting,
+            level,
+            stats: definition.stats_at(level),
+            hit_interval: definition.hit_interval,
+            knockback: definition.knockback,
+            stun: definition.stun,
+            on_hit: definition.on_hit,
+        }
+    }
+
+    pub fn set_level(&mut self, definition: &WeaponDefinition, level: usize) {
+        self.level = level.min(definition.max_level());
+        self.stats = definition.stats_at(self.level);
+    }
+
+    pub fn damage(&self, attack: &Attack) -> f32 {
+        self.stats.damage * attack.damage_scale
+    }
+
+    pub fn area(&self, attack: &Attack) -> f32 {
+        self.stats.area * attack.area_scale
+    }
+
+    pub fn cooldown(&self, attack: &Attack) -> f32 {
+        (self.stats.cooldown * attack.cooldown_scale).max(MIN_COOLDOWN)
+    }
+
+    // An aura's cooldown is how often it hits, everything else fires on its cooldown instead
+    pub fn hit_interval(&self, attack: &Attack) -> f32 {
+        match self.kind {
+            WeaponKind::Aura => self.cooldown(attack),
+            _ => self.hit_interval,
+        }
+    }
+
+    pub fn amount(&self, attack: &Attack) -> u32 {
+        self.stats.amount + attack.extra_amount
+    }
+
+    pub fn duration(&self, attack: &Attack) -> f32 {
+        self.stats.duration * attack.duration_scale
+    }
+
+    // An aura's duration is how long its status effect lasts, other weapons use it for
+    // how long their hitboxes stay out
+    pub fn status(&self, attack: &Attack) -> Option<StatusApplication> {
+        self.on_hit.map(|application| StatusApplication {
+            duration: match self.kind {
+                WeaponKind::Aura => self.duration(attack),
+                _ => application.duration * attack.duration_scale,
+            },
+            ..application
+        })
+    }
+}
+
+// A collider that deals its weapon's damage; an aura is its own hitbox
+#[derive(Component)]
+pub struct Hitbox(pub Entity);
+
+#[derive(Component)]
+struct HitboxLifetime(Timer);
+
+// How a weapon that fires on a cooldown draws its hitboxes
+#[derive(Component)]
+struct WeaponSprite {
+    texture: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    audio: Handle<AudioSource>,
+    frames: AnimationIndices,
+    frame_time: f32,
+    scale: f32,
+    collider_radius: f32,
+    color: Color,
+}
+
+#[derive(Component)]
+struct WeaponCooldown(Timer);
+
+#[derive(Component)]
+struct Satellite {
+    angle: f32,
+}
+
+// Offset from the pawn a slash stays at while it plays out
+#[derive(Component)]
+struct Slash(Vec2);
+
 #[derive(Bundle)]
 pub struct WeaponBundle {
     sprite: SpriteSheetBundle,
@@ -45,135 +342,396 @@ pub struct WeaponBundle {
     weapon: Weapon,
 }
 
-impl Default for WeaponBundle {
-    fn default() -> Self {
-        WeaponBundle {
-            sprite: SpriteSheetBundle {
-                transform: Transform::from_translation(STARTING_POSITION),
-                ..default()
-            },
-            animation_indices: AnimationIndices { first: 0, last: 0 },
-            animation_timer: AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
-            weapon: Weapon {
-                audio_filename: String::from(""),
-                filename: String::from(""),
-                damage_frame_start: 0,
-                damage_frame_end: 0,
-            },
+fn load_weapon_definitions(mut commands: Commands) {
+    let data: WeaponData = ron::from_str(include_str!("../assets/data/weapons.ron"))
+        .expect("Failed to parse weapon definitions");
+
+    for (id, definition) in &data.weapons {
+        if definition.levels.len() >= MAX_WEAPON_LEVEL {
+            warn!("Weapon {id} has more than {MAX_WEAPON_LEVEL} levels, extras are ignored");
+        }
+        if let Some(evolution) = &definition.evolution {
+            if !data.weapons.contains_key(&evolution.into) {
+                warn!("Weapon {id} evolves into unknown weapon {}", evolution.into);
+            }
+            if !data.passives.contains_key(&evolution.passive) {
+                warn!(
+                    "Weapon {id} evolves with unknown passive {}",
+                    evolution.passive
+                );
+            }
         }
     }
+
+    commands.insert_resource(WeaponDefinitions(data.weapons));
+    commands.insert_resource(PassiveDefinitions(data.passives));
 }
 
-pub fn setup_sprite(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    settings: Res<Settings>,
-) {
-    let weapon = weapon_01();
-    let texture = asset_server.load(&weapon.filename);
-    let layout = TextureAtlasLayout::from_grid(Vec2::new(64., 64.), 8, 3, None, None);
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let animation_indices = AnimationIndices { first: 0, last: 23 };
-    let audio = asset_server.load(&weapon.audio_filename);
+// Every weapon sheet is laid out the same way
+pub fn weapon_sheet_layout() -> TextureAtlasLayout {
+    TextureAtlasLayout::from_grid(Vec2::new(64., 64.), 8, 3, None, None)
+}
+
+pub fn spawn_weapon(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+    settings: &Settings,
+    id: &str,
+    definition: &WeaponDefinition,
+) -> Entity {
+    let texture = definition.texture(asset_server);
+    let texture_atlas_layout = texture_atlas_layouts.add(weapon_sheet_layout());
+    let audio = asset_server.load(&definition.audio_filename);
+    let color = definition.color();
+    let weapon = Weapon::new(id, definition, 1);
+
+    if definition.kind != WeaponKind::Aura {
+        return commands
+            .spawn((
+                weapon,
+                WeaponSprite {
+                    texture,
+                    layout: texture_atlas_layout,
+                    audio,
+                    frames: definition.frames.clone(),
+                    frame_time: definition.frame_time,
+                    scale: definition.scale,
+                    collider_radius: definition.collider_radius,
+                    color,
+                },
+                WeaponCooldown(Timer::from_seconds(0., TimerMode::Once)),
+            ))
+            .id();
+    }
 
     let mut transform = Transform::from_translation(STARTING_POSITION);
     transform.translation.z = 0.;
-    transform.scale = Vec3::splat(2.);
+    transform.scale = Vec3::splat(definition.scale);
 
-    commands.spawn((
-        WeaponBundle {
-            sprite: SpriteSheetBundle {
-                texture,
-                atlas: TextureAtlas {
-                    layout: texture_atlas_layout,
-                    index: animation_indices.first,
+    let entity = commands
+        .spawn((
+            WeaponBundle {
+                sprite: SpriteSheetBundle {
+                    sprite: Sprite { color, ..default() },
+                    texture,
+                    atlas: TextureAtlas {
+                        layout: texture_atlas_layout,
+                        index: definition.frames.first,
+                    },
+                    transform,
+                    ..default()
                 },
-                transform,
+                animation_indices: definition.frames.clone(),
+                animation_timer: AnimationTimer(Timer::from_seconds(
+                    definition.frame_time,
+                    TimerMode::Repeating,
+                )),
+                weapon,
+            },
+            RigidBody::KinematicPositionBased,
+            Collider::ball(definition.collider_radius),
+            ActiveEvents::COLLISION_EVENTS,
+            Sensor,
+            SolverGroups::new(ENEMY_WEAPON_GROUP, Group::default()),
+            AudioBundle {
+                source: audio,
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Loop,
+                    volume: Volume::new(settings.volume),
+                    ..default()
+                },
+            },
+        ))
+        .id();
+    commands.entity(entity).insert(Hitbox(entity));
+    entity
+}
+
+fn spawn_hitbox<'a>(
+    commands: &'a mut Commands,
+    weapon: Entity,
+    sprite: &WeaponSprite,
+    position: Vec2,
+    area: f32,
+    lifetime: f32,
+    flip_x: bool,
+) -> bevy::ecs::system::EntityCommands<'a> {
+    commands.spawn((
+        SpriteSheetBundle {
+            sprite: Sprite {
+                color: sprite.color,
+                flip_x,
                 ..default()
             },
-            animation_indices,
-            animation_timer: AnimationTimer(Timer::from_seconds(0.05, TimerMode::Repeating)),
-            weapon,
+            texture: sprite.texture.clone(),
+            atlas: TextureAtlas {
+                layout: sprite.layout.clone(),
+                index: sprite.frames.first,
+            },
+            transform: Transform::from_translation(position.extend(1.))
+                .with_scale(Vec3::splat(sprite.scale * area)),
+            ..default()
         },
+        sprite.frames.clone(),
+        AnimationTimer(Timer::from_seconds(sprite.frame_time, TimerMode::Repeating)),
+        Hitbox(weapon),
+        HitboxLifetime(Timer::from_seconds(lifetime, TimerMode::Once)),
         RigidBody::KinematicPositionBased,
-        Collider::ball(32.),
+        Collider::ball(sprite.collider_radius),
         ActiveEvents::COLLISION_EVENTS,
         Sensor,
         SolverGroups::new(ENEMY_WEAPON_GROUP, Group::default()),
-        AudioBundle {
-            source: audio,
-            settings: PlaybackSettings {
-                mode: PlaybackMode::Loop,
-                volume: Volume::new(settings.volume),
-                ..default()
-            },
-        },
-    ));
+    ))
 }
 
-fn update_volume(
+pub fn setup_sprite(
     mut commands: Commands,
-    mut weapon: Query<Entity, With<Weapon>>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     settings: Res<Settings>,
+    definitions: Res<WeaponDefinitions>,
+    mut passives: ResMut<Passives>,
 ) {
-    for entity in &mut weapon {
-        commands.entity(entity).insert(PlaybackSettings {
-            volume: Volume::new(settings.volume),
-            mode: PlaybackMode::Loop,
-            ..default()
-        });
+    passives.clear();
+
+    let Some(definition) = definitions.get(STARTING_WEAPON) else {
+        warn!("Unknown starting weapon: {STARTING_WEAPON}");
+        return;
+    };
+    spawn_weapon(
+        &mut commands,
+        &asset_server,
+        &mut texture_atlas_layouts,
+        &settings,
+        STARTING_WEAPON,
+        definition,
+    );
+}
+
+// Looping weapon sounds are already playing, so the new volume goes to their sinks
+fn update_volume(
+    settings: Res<Settings>,
+    weapons: Query<&AudioSink, (With<Weapon>, With<Handle<AudioSource>>)>,
+) {
+    for sink in &weapons {
+        sink.set_volume(settings.volume);
     }
 }
 
+// Rebuilds the pawn's attack modifiers from the passives it owns
+fn apply_passives(
+    passives: Res<Passives>,
+    definitions: Res<PassiveDefinitions>,
+    mut attack: ResMut<Attack>,
+) {
+    let mut bonus = PassiveBonus::default();
+    for (id, &level) in passives.iter() {
+        let Some(definition) = definitions.get(id) else {
+            continue;
+        };
+        let level = level as f32;
+        bonus.damage += definition.per_level.damage * level;
+        bonus.area += definition.per_level.area * level;
+        bonus.cooldown += definition.per_level.cooldown * level;
+        bonus.duration += definition.per_level.duration * level;
+        bonus.amount += definition.per_level.amount * level as u32;
+        bonus.crit_chance += definition.per_level.crit_chance * level;
+        bonus.magnet += definition.per_level.magnet * level;
+    }
+
+    let base = Attack::default();
+    *attack = Attack {
+        damage_scale: base.damage_scale + bonus.damage,
+        area_scale: base.area_scale + bonus.area,
+        cooldown_scale: (base.cooldown_scale - bonus.cooldown).max(0.),
+        duration_scale: base.duration_scale + bonus.duration,
+        extra_amount: base.extra_amount + bonus.amount,
+        crit_chance: base.crit_chance + bonus.crit_chance,
+        magnet_scale: base.magnet_scale + bonus.magnet,
+        ..base
+    };
+}
+
 pub fn move_weapon(
+    attack: Res<Attack>,
+    definitions: Res<WeaponDefinitions>,
     mut query: Query<(&mut Transform, &Weapon), Without<Pawn>>,
     pawn_query: Query<&Transform, With<Pawn>>,
 ) {
-    for mut transform in &mut query {
-        let pawn_transform = pawn_query.single();
-        transform.0.translation = pawn_transform.translation.truncate().extend(1.);
+    let Ok(pawn_transform) = pawn_query.get_single() else {
+        return;
+    };
+
+    for (mut transform, weapon) in &mut query {
+        let scale = definitions
+            .get(&weapon.id)
+            .map_or(1., |definition| definition.scale);
+        transform.translation = pawn_transform.translation.truncate().extend(1.);
+        transform.scale = Vec3::splat(scale * weapon.area(&attack));
     }
 }
 
-pub fn cleanup_sprite(mut commands: Commands, mut query: Query<Entity, With<Weapon>>) {
-    for entity in &mut query {
-        commands.entity(entity).despawn();
+fn fire_weapons(
+    mut commands: Commands,
+    time: Res<Time>,
+    attack: Res<Attack>,
+    settings: Res<Settings>,
+    mut weapons: Query<(Entity, &Weapon, &WeaponSprite, &mut WeaponCooldown)>,
+    player: Query<(&Transform, &Direction), With<Pawn>>,
+    targeting: Targeting,
+) {
+    let Ok((player, direction)) = player.get_single() else {
+        return;
+    };
+    let player_pos = player.translation.truncate();
+
+    for (entity, weapon, sprite, mut cooldown) in &mut weapons {
+        if !cooldown.0.tick(time.delta()).finished() {
+            continue;
+        }
+
+        // The next volley waits for this one to play out
+        let duration = weapon.duration(&attack);
+        cooldown.0 = Timer::from_seconds(duration + weapon.cooldown(&attack), TimerMode::Once);
+
+        let amount = weapon.amount(&attack);
+        let area = weapon.area(&attack);
+
+        match weapon.kind {
+            // Auras hit through their own hitbox and are spawned without a WeaponCooldown
+            WeaponKind::Aura => unreachable!("aura weapons are never fired"),
+            WeaponKind::Orbit { radius, .. } => {
+                for index in 0..amount {
+                    let angle = std::f32::consts::TAU * index as f32 / amount as f32;
+                    let position = player_pos + Vec2::from_angle(angle) * radius * area;
+                    spawn_hitbox(
+                        &mut commands,
+                        entity,
+                        sprite,
+                        position,
+                        area,
+                        duration,
+                        false,
+                    )
+                    .insert(Satellite { angle });
+                }
+            }
+            WeaponKind::Slash { reach } => {
+                for index in 0..amount {
+                    let facing = if index % 2 == 0 {
+                        direction.vector()
+                    } else {
+                        -direction.vector()
+                    };
+                    // Extra slashes on the same side stack upwards
+                    let offset =
+                        facing * reach * area + Vec2::Y * (index / 2) as f32 * sprite.scale * 16.;
+                    spawn_hitbox(
+                        &mut commands,
+                        entity,
+                        sprite,
+                        player_pos + offset,
+                        area,
+                        duration,
+                        facing.x < 0.,
+                    )
+                    .insert(Slash(offset));
+                }
+            }
+            WeaponKind::GroundTarget { range } => {
+                let targets = targeting.aim(weapon.targeting, player_pos, range, amount as usize);
+                for (index, position) in targets.into_iter().enumerate() {
+                    // Spread out hits that share an aim point
+                    let spread = if index == 0 {
+                        Vec2::ZERO
+                    } else {
+                        Vec2::from_angle(fastrand::f32() * std::f32::consts::TAU)
+                            * sprite.collider_radius
+                            * sprite.scale
+                            * area
+                    };
+                    spawn_hitbox(
+                        &mut commands,
+                        entity,
+                        sprite,
+                        position + spread,
+                        area,
+                        duration,
+                        false,
+                    );
+                }
+            }
+        }
+
+        commands.spawn(AudioBundle {
+            source: sprite.audio.clone(),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new(settings.volume),
+                ..default()
+            },
+        });
     }
 }
 
-fn weapon_01() -> Weapon {
-    Weapon {
-        audio_filename: String::from("sfx/woosh2.ogg"),
-        filename: String::from("magic/241.png"),
-        damage_frame_start: 4,
-        damage_frame_end: 18,
+fn orbit_satellites(
+    time: Res<Time>,
+    attack: Res<Attack>,
+    weapons: Query<&Weapon>,
+    mut satellites: Query<(&mut Transform, &mut Satellite, &Hitbox), Without<Pawn>>,
+    player: Query<&Transform, With<Pawn>>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    for (mut transform, mut satellite, hitbox) in &mut satellites {
+        let Ok(weapon) = weapons.get(hitbox.0) else {
+            continue;
+        };
+        let WeaponKind::Orbit { radius, speed } = weapon.kind else {
+            continue;
+        };
+
+        satellite.angle += speed * time.delta_seconds();
+        let offset = Vec2::from_angle(satellite.angle) * radius * weapon.area(&attack);
+        transform.translation = (player.translation.truncate() + offset).extend(1.);
     }
 }
 
-fn weapon_02() -> Weapon {
-    Weapon {
-        audio_filename: String::from("sfx/woosh2.ogg"),
-        filename: String::from("magic/242.png"),
-        damage_frame_start: 0,
-        damage_frame_end: 23,
+fn follow_slashes(
+    mut slashes: Query<(&mut Transform, &Slash), Without<Pawn>>,
+    player: Query<&Transform, With<Pawn>>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    for (mut transform, slash) in &mut slashes {
+        transform.translation = (player.translation.truncate() + slash.0).extend(1.);
     }
 }
 
-fn weapon_03() -> Weapon {
-    Weapon {
-        audio_filename: String::from("sfx/woosh2.ogg"),
-        filename: String::from("magic/243.png"),
-        damage_frame_start: 0,
-        damage_frame_end: 23,
+fn expire_hitboxes(
+    mut commands: Commands,
+    time: Res<Time>,
+    weapons: Query<(), With<Weapon>>,
+    mut hitboxes: Query<(Entity, &Hitbox, &mut HitboxLifetime)>,
+) {
+    for (entity, hitbox, mut lifetime) in &mut hitboxes {
+        // Hitboxes of an evolved or removed weapon go with it
+        if lifetime.0.tick(time.delta()).finished() || !weapons.contains(hitbox.0) {
+            commands.entity(entity).despawn();
+        }
     }
 }
 
-fn weapon_04() -> Weapon {
-    Weapon {
-        audio_filename: String::from("sfx/woosh2.ogg"),
-        filename: String::from("magic/244.png"),
-        damage_frame_start: 0,
-        damage_frame_end: 23,
+pub fn cleanup_sprite(
+    mut commands: Commands,
+    mut query: Query<Entity, Or<(With<Weapon>, With<Hitbox>)>>,
+) {
+    for entity in &mut query {
+        commands.entity(entity).despawn();
     }
 }